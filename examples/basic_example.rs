@@ -19,7 +19,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         version: "1.0.0".to_string(),
     };
 
-    let mut client = MCPClient::new(transport, client_info);
+    let client = MCPClient::new(transport, client_info);
     client.initialize().await?;
     println!("✓ Connected to MCP server");
 
@@ -32,7 +32,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // List available resources
     println!("\nAvailable resources:");
-    let (resources, _) = client.list_resources().await?;
+    let resources = client.list_resources().await?;
     for resource in resources {
         println!("- {} ({})", resource.name, resource.uri);
     }
@@ -26,7 +26,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         Err(e) => logger.warn(&format!("Could not list models: {}", e)),
     }
 
-    let mut security = SecurityManager::new();
+    let _security = SecurityManager::new();
     let mut messages: Vec<OllamaMessage> = Vec::new();
 
     println!("=== MCP LLM Application with Ollama ===");
@@ -20,7 +20,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         version: "1.0.0".to_string(),
     };
 
-    let mut client1 = MCPClient::new(transport1, client_info1);
+    let client1 = MCPClient::new(transport1, client_info1);
     client1.initialize().await?;
     println!("✓ Connected to main server");
 
@@ -33,19 +33,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Call a tool from server 1
     println!("\nCalling tool from server 1:");
-    let result = client1.call_tool(
+    let result = client1.call_tool_checked(
         "greet",
         json!({
             "name": "Multi-Server Test"
         })
     ).await?;
     
-    match &result.content[0] {
-        mcp_client_rust::types::ToolResultContent::Text { text } => {
-            println!("Result: {}", text);
-        }
-        _ => println!("Received non-text result"),
-    }
+    println!("Result: {}", result.text());
 
     client1.close().await?;
     println!("\n✓ Multi-server example completed successfully");
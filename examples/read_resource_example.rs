@@ -2,7 +2,7 @@ use mcp_client_rust::client::MCPClient;
 use mcp_client_rust::transport::StdioTransport;
 use mcp_client_rust::transport::Transport;
 use mcp_client_rust::types::ClientInfo;
-use mcp_client_rust::types::ContentItem;
+use mcp_client_rust::types::ResourceContents;
 use std::sync::Arc;
 
 #[tokio::main]
@@ -20,7 +20,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         version: "1.0.0".to_string(),
     };
 
-    let mut client = MCPClient::new(transport, client_info);
+    let client = MCPClient::new(transport, client_info);
     client.initialize().await?;
     println!("✓ Connected to MCP server");
 
@@ -39,10 +39,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         println!("\n✓ File content retrieved successfully:\n---");
         for item in resource_content.contents {
             match item {
-                ContentItem::Text { text } => {
+                ResourceContents::Text { text, .. } => {
                     println!("{}", text);
                 }
-                ContentItem::Blob { blob: _ } => {
+                ResourceContents::Blob { .. } => {
                     println!("[Binary content skipped]"); // Or handle blobs differently
                 }
             }
@@ -1,10 +1,10 @@
 use mcp_client_rust::{
-    client::MCPClient,
+    client::{ClientError, MCPClient},
     transport::StdioTransport,
-    types::{ClientInfo, Tool},
+    types::ClientInfo,
 };
 use std::sync::Arc;
-use tokio::time::{timeout, Duration};
+use std::time::Duration;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -29,68 +29,70 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     };
     
-    // Create client
-    let mut client = MCPClient::new(transport, client_info);
+    // Create client with request timeouts
+    let client = MCPClient::new(transport, client_info);
+    client.set_default_timeout(Some(Duration::from_secs(5)));
+    client.set_method_timeout("initialize", Duration::from_secs(10));
     
-    // Initialize connection with timeout
+    // Initialize connection
     println!("Initializing connection...");
-    match timeout(Duration::from_secs(10), client.initialize()).await {
-        Ok(Ok(())) => println!("✓ Connection initialized successfully"),
-        Ok(Err(e)) => {
-            eprintln!("✗ Failed to initialize: {}", e);
-            return Err(e.into());
-        }
-        Err(_) => {
+    match client.initialize().await {
+        Ok(()) => println!("✓ Connection initialized successfully"),
+        Err(ClientError::Timeout { .. }) => {
             eprintln!("✗ Connection timeout");
             return Err("Connection timeout".into());
         }
+        Err(e) => {
+            eprintln!("✗ Failed to initialize: {}", e);
+            return Err(e.into());
+        }
     }
     
     // List available tools
     println!("Listing available tools...");
-    match timeout(Duration::from_secs(5), client.list_tools()).await {
-        Ok(Ok(tools)) => {
+    match client.list_tools().await {
+        Ok(tools) => {
             println!("✓ Found {} tools:", tools.len());
             for tool in tools {
                 println!("  - {}: {}", tool.name, tool.description.unwrap_or_default());
             }
         }
-        Ok(Err(e)) => {
-            eprintln!("✗ Failed to list tools: {}", e);
-            return Err(e.into());
-        }
-        Err(_) => {
+        Err(ClientError::Timeout { .. }) => {
             eprintln!("✗ Tools listing timeout");
             return Err("Tools listing timeout".into());
         }
+        Err(e) => {
+            eprintln!("✗ Failed to list tools: {}", e);
+            return Err(e.into());
+        }
     }
     
     // Test calling a tool
     println!("Testing tool call (greet)...");
     let args = serde_json::json!({"name": "Test User"});
     
-    match timeout(Duration::from_secs(5), client.call_tool("greet", args)).await {
-        Ok(Ok(result)) => {
+    match client.call_tool("greet", args).await {
+        Ok(result) => {
             println!("✓ Tool call successful:");
             for content in result.content {
                 match content {
-                    mcp_client_rust::types::ToolResultContent::Text { text } => {
+                    mcp_client_rust::types::Content::Text { text, .. } => {
                         println!("  Result: {}", text);
                     }
-                    mcp_client_rust::types::ToolResultContent::Blob { blob } => {
-                        println!("  Binary result: {} bytes", blob.len());
+                    other => {
+                        println!("  Non-text result: {:?}", other.mime_type());
                     }
                 }
             }
         }
-        Ok(Err(e)) => {
-            eprintln!("✗ Tool call failed: {}", e);
-            return Err(e.into());
-        }
-        Err(_) => {
+        Err(ClientError::Timeout { .. }) => {
             eprintln!("✗ Tool call timeout");
             return Err("Tool call timeout".into());
         }
+        Err(e) => {
+            eprintln!("✗ Tool call failed: {}", e);
+            return Err(e.into());
+        }
     }
     
     // Close connection
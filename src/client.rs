@@ -1,9 +1,21 @@
-use crate::transport::Transport;
+use crate::elicitation::ElicitationHandler;
+use crate::logging::McpLogger;
+use crate::notifications::ServerNotification;
+use crate::sampling::SamplingHandler;
+use crate::subscription::ResourceSubscription;
+use crate::transport::{Transport, TransportError};
 use crate::types::*;
+use futures::stream::{self, FuturesUnordered, Stream, StreamExt, TryStreamExt};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
 use serde_json::{json, Value};
-use std::sync::atomic::{AtomicU64, Ordering};
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock, Weak};
+use std::time::{Duration, Instant};
 use thiserror::Error;
+use tokio::sync::{broadcast, mpsc, oneshot, watch};
+use tokio::task::JoinHandle;
 
 #[derive(Error, Debug)]
 pub enum ClientError {
@@ -13,88 +25,703 @@ pub enum ClientError {
     JsonError(#[from] serde_json::Error),
     #[error("Request failed: {0}")]
     RequestFailed(String),
+    /// The server answered with a JSON-RPC error.
+    #[error("Server returned error: {0}")]
+    Rpc(JsonRpcError),
+    /// The tool ran but reported failure (`isError: true`).
+    #[error("Tool {tool} failed: {}", .result.text())]
+    ToolExecution { tool: String, result: ToolResult },
+    #[error("Tool {0} returned no structured content")]
+    MissingStructuredContent(String),
     #[error("Tool not found: {0}")]
     ToolNotFound(String),
     #[error("Resource not found: {0}")]
     ResourceNotFound(String),
+    #[error("Client is not initialized")]
+    NotInitialized,
+    #[error("Client is already initialized")]
+    AlreadyInitialized,
+    /// `initialize` was called while another handshake was under way.
+    #[error("Client is already initializing")]
+    InitializeInProgress,
+    #[error("Client is closed")]
+    Closed,
+    #[error("Server does not support {0}")]
+    CapabilityNotSupported(String),
+    #[error("Unsupported protocol version: {0}")]
+    UnsupportedProtocolVersion(String),
+    #[error("Roots must be file:// URIs, got {0}")]
+    InvalidRoot(String),
+    #[error("Invalid URI template: {0}")]
+    UriTemplate(#[from] crate::uri_template::UriTemplateError),
+    #[error("Request cancelled: {0}")]
+    Cancelled(String),
+    #[error("Request {method} timed out after {elapsed:?}")]
+    Timeout { method: String, elapsed: Duration },
+    #[error("{method} returned more than {limit} pages")]
+    PageLimitExceeded { method: String, limit: usize },
 }
 
 pub type ClientResult<T> = Result<T, ClientError>;
 
+impl ClientError {
+    /// The server's JSON-RPC error, if that is what this is.
+    pub fn rpc_error(&self) -> Option<&JsonRpcError> {
+        match self {
+            ClientError::Rpc(error) => Some(error),
+            _ => None,
+        }
+    }
+
+    pub fn is_method_not_found(&self) -> bool {
+        self.rpc_error().is_some_and(JsonRpcError::is_method_not_found)
+    }
+
+    pub fn is_invalid_params(&self) -> bool {
+        self.rpc_error().is_some_and(JsonRpcError::is_invalid_params)
+    }
+
+    pub fn is_internal_error(&self) -> bool {
+        self.rpc_error().is_some_and(JsonRpcError::is_internal_error)
+    }
+
+    pub fn is_resource_not_found(&self) -> bool {
+        self.rpc_error().is_some_and(JsonRpcError::is_resource_not_found)
+    }
+}
+
+/// Lifecycle of an MCP session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionState {
+    /// Created, `initialize` not called yet.
+    Uninitialized,
+    /// `initialize` sent, waiting for the server's answer.
+    Initializing,
+    /// Handshake complete; requests may be sent.
+    Ready,
+    /// `close` in progress.
+    Closing,
+    /// Closed by `close` or because the connection dropped.
+    Closed,
+}
+
+/// Number of notifications buffered per subscriber before the slowest one lags.
+const NOTIFICATION_CAPACITY: usize = 256;
+
+/// Request timeout used when `MCP_TIMEOUT_SECONDS` is not set.
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Upper bound on pages fetched by the auto-paginating list helpers, so a
+/// server that keeps returning cursors can't loop forever.
+pub const MAX_PAGES: usize = 1000;
+
+/// Methods without side effects, which `MCPClient::batch` may safely send
+/// again after the server rejects a batch.
+const READ_ONLY_METHODS: &[&str] = &[
+    "ping",
+    "tools/list",
+    "resources/list",
+    "resources/templates/list",
+    "resources/read",
+    "prompts/list",
+    "prompts/get",
+    "completion/complete",
+];
+
+/// One request in a batch sent with `MCPClient::batch`.
+#[derive(Debug, Clone)]
+pub struct BatchRequest {
+    pub method: String,
+    pub params: Option<Value>,
+}
+
+impl BatchRequest {
+    pub fn new(method: &str, params: Option<Value>) -> Self {
+        BatchRequest {
+            method: method.to_string(),
+            params,
+        }
+    }
+}
+
+/// Receives `notifications/progress` updates for a single request.
+///
+/// Handlers run in order on a task of their own, so a slow handler delays
+/// only later updates for its request. Updates that arrived before the
+/// result may still be delivered after the call returns.
+pub type ProgressHandler = Arc<dyn Fn(ProgressNotification) + Send + Sync>;
+
+/// Per-call settings for requests made through the `*_with_options` methods.
+#[derive(Clone, Default)]
+pub struct RequestOptions {
+    /// Overrides the client's default and per-method timeouts for this call.
+    pub timeout: Option<Duration>,
+    pub cancellation: Option<CancellationToken>,
+    /// When set, the request carries a progress token and the server's
+    /// progress updates for it are passed to this handler.
+    pub on_progress: Option<ProgressHandler>,
+}
+
+impl RequestOptions {
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    pub fn with_progress(mut self, handler: impl Fn(ProgressNotification) + Send + Sync + 'static) -> Self {
+        self.on_progress = Some(Arc::new(handler));
+        self
+    }
+}
+
+/// Cancels the requests it was passed to, from any task.
+///
+/// Cancelling sends `notifications/cancelled` to the server and makes the
+/// pending call return `ClientError::Cancelled`. Dropping a call future has
+/// the same effect on the server side.
+#[derive(Debug, Clone)]
+pub struct CancellationToken {
+    reason: Arc<watch::Sender<Option<String>>>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        CancellationToken {
+            reason: Arc::new(watch::channel(None).0),
+        }
+    }
+
+    pub fn cancel(&self, reason: impl Into<String>) {
+        self.reason.send_replace(Some(reason.into()));
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.reason.borrow().is_some()
+    }
+
+    pub fn reason(&self) -> Option<String> {
+        self.reason.borrow().clone()
+    }
+
+    /// Resolves with the reason once `cancel` has been called.
+    async fn cancelled(&self) -> String {
+        let mut receiver = self.reason.subscribe();
+        // The sender lives as long as `self`, so waiting can't fail.
+        let reason = receiver
+            .wait_for(Option::is_some)
+            .await
+            .ok()
+            .and_then(|reason| reason.clone());
+        reason.unwrap_or_default()
+    }
+}
+
+impl Default for CancellationToken {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Handle to an MCP session.
+///
+/// Cloning is cheap and every clone talks to the same connection, so a client
+/// can be shared across tasks and used concurrently.
+#[derive(Clone)]
 pub struct MCPClient {
-    transport: Arc<dyn Transport>,
+    inner: Arc<ClientInner>,
+}
+
+struct ClientInner {
+    shared: Arc<Shared>,
     client_info: ClientInfo,
-    server_info: Option<ServerInfo>,
-    request_id: Arc<AtomicU64>,
+    timeouts: RwLock<Timeouts>,
+    request_id: AtomicU64,
+    reader: JoinHandle<()>,
+    keepalive: Mutex<Option<JoinHandle<()>>>,
+    /// Set once the server has rejected a batch; later batches go out as
+    /// individual requests.
+    batches_rejected: AtomicBool,
+    /// Subscribed resource URIs and how many `ResourceSubscription`s share each.
+    subscriptions: Mutex<HashMap<String, usize>>,
+}
+
+struct Timeouts {
+    /// `None` lets requests wait forever.
+    default: Option<Duration>,
+    per_method: HashMap<String, Duration>,
+}
+
+impl Timeouts {
+    fn from_env() -> Self {
+        let default = std::env::var("MCP_TIMEOUT_SECONDS")
+            .ok()
+            .and_then(|secs| secs.parse().ok())
+            .map(Duration::from_secs)
+            .unwrap_or(DEFAULT_REQUEST_TIMEOUT);
+
+        Timeouts {
+            default: Some(default),
+            per_method: HashMap::new(),
+        }
+    }
+}
+
+/// State shared between the client handle and its background reader.
+struct Shared {
+    transport: Arc<dyn Transport>,
+    state: Mutex<SessionState>,
+    initialize_result: RwLock<Option<InitializeResult>>,
+    /// Also receives the server's `notifications/message` log entries.
+    logger: RwLock<Option<Arc<McpLogger>>>,
+    /// Roots listed to the server in reply to `roots/list`.
+    roots: RwLock<Vec<Root>>,
+    /// Answers the server's `sampling/createMessage` requests.
+    sampling: RwLock<Option<Arc<dyn SamplingHandler>>>,
+    /// Answers the server's `elicitation/create` requests.
+    elicitation: RwLock<Option<Arc<dyn ElicitationHandler>>>,
+    /// In-flight requests keyed by JSON-RPC id, completed by the reader.
+    pending: Mutex<HashMap<u64, oneshot::Sender<JsonRpcResponse>>>,
+    /// Batches waiting for their replies, told when the server answers a
+    /// batch with a single id-less error, i.e. rejects batching.
+    batch_rejections: Mutex<Vec<oneshot::Sender<JsonRpcError>>>,
+    /// Senders feeding each request's progress handler task, keyed by
+    /// progress token, which is the request id.
+    progress: Mutex<HashMap<u64, mpsc::UnboundedSender<ProgressNotification>>>,
+    /// Taken when the session ends so subscribers see `RecvError::Closed`.
+    notifications: Mutex<Option<broadcast::Sender<ServerNotification>>>,
 }
 
 impl MCPClient {
+    /// Creates a client and starts its background reader.
+    ///
+    /// Must be called from within a Tokio runtime.
     pub fn new(
         transport: Arc<dyn Transport>,
         client_info: ClientInfo,
     ) -> Self {
-        MCPClient {
+        let shared = Arc::new(Shared {
             transport,
-            client_info,
-            server_info: None,
-            request_id: Arc::new(AtomicU64::new(1)),
+            state: Mutex::new(SessionState::Uninitialized),
+            initialize_result: RwLock::new(None),
+            logger: RwLock::new(None),
+            roots: RwLock::new(Vec::new()),
+            sampling: RwLock::new(None),
+            elicitation: RwLock::new(None),
+            pending: Mutex::new(HashMap::new()),
+            batch_rejections: Mutex::new(Vec::new()),
+            progress: Mutex::new(HashMap::new()),
+            notifications: Mutex::new(Some(broadcast::channel(NOTIFICATION_CAPACITY).0)),
+        });
+        let reader = tokio::spawn(shared.clone().read_loop());
+
+        MCPClient {
+            inner: Arc::new(ClientInner {
+                shared,
+                client_info,
+                timeouts: RwLock::new(Timeouts::from_env()),
+                request_id: AtomicU64::new(1),
+                reader,
+                keepalive: Mutex::new(None),
+                batches_rejected: AtomicBool::new(false),
+                subscriptions: Mutex::new(HashMap::new()),
+            }),
         }
     }
 
-    pub async fn initialize(&mut self) -> ClientResult<()> {
-        let params = json!({
-            "protocolVersion": "2024-11-05",
-            "capabilities": {
-                "tools": {},
-                "resources": {},
-                "prompts": {}
-            },
-            "clientInfo": {
-                "name": self.client_info.name,
-                "version": self.client_info.version
+    pub fn client_info(&self) -> &ClientInfo {
+        &self.inner.client_info
+    }
+
+    /// Timeout for requests without a per-method or per-call override.
+    ///
+    /// Starts at `MCP_TIMEOUT_SECONDS` if set, otherwise
+    /// `DEFAULT_REQUEST_TIMEOUT`. `None` disables the timeout.
+    pub fn set_default_timeout(&self, timeout: Option<Duration>) {
+        self.inner.timeouts.write().unwrap().default = timeout;
+    }
+
+    pub fn default_timeout(&self) -> Option<Duration> {
+        self.inner.timeouts.read().unwrap().default
+    }
+
+    /// Overrides the default timeout for one method, e.g. `tools/call`.
+    pub fn set_method_timeout(&self, method: &str, timeout: Duration) {
+        self.inner
+            .timeouts
+            .write()
+            .unwrap()
+            .per_method
+            .insert(method.to_string(), timeout);
+    }
+
+    pub fn clear_method_timeout(&self, method: &str) {
+        self.inner.timeouts.write().unwrap().per_method.remove(method);
+    }
+
+    fn timeout_for(&self, method: &str, options: &RequestOptions) -> Option<Duration> {
+        let timeouts = self.inner.timeouts.read().unwrap();
+        options
+            .timeout
+            .or_else(|| timeouts.per_method.get(method).copied())
+            .or(timeouts.default)
+    }
+
+    pub fn state(&self) -> SessionState {
+        *self.inner.shared.state.lock().unwrap()
+    }
+
+    /// The server's answer to `initialize`, once the handshake has succeeded.
+    pub fn initialize_result(&self) -> Option<InitializeResult> {
+        self.inner.shared.initialize_result.read().unwrap().clone()
+    }
+
+    pub fn server_info(&self) -> Option<ServerInfo> {
+        self.initialize_result().map(|result| result.server_info)
+    }
+
+    pub fn server_capabilities(&self) -> Option<Capabilities> {
+        self.initialize_result().map(|result| result.capabilities)
+    }
+
+    /// The protocol version agreed on during `initialize`.
+    pub fn protocol_version(&self) -> Option<ProtocolVersion> {
+        self.initialize_result()
+            .and_then(|result| result.protocol_version.parse().ok())
+    }
+
+    /// Usage hints the server provided for the model, if any.
+    pub fn instructions(&self) -> Option<String> {
+        self.initialize_result().and_then(|result| result.instructions)
+    }
+
+    /// Subscribes to notifications sent by the server.
+    ///
+    /// Only notifications received after subscribing are delivered. A receiver
+    /// that falls more than a few hundred messages behind gets `RecvError::Lagged`.
+    pub fn subscribe_notifications(&self) -> broadcast::Receiver<ServerNotification> {
+        match &*self.inner.shared.notifications.lock().unwrap() {
+            Some(sender) => sender.subscribe(),
+            // Already closed: hand out a receiver whose sender is gone.
+            None => broadcast::channel(1).1,
+        }
+    }
+
+    /// Performs the initialize handshake and sends `notifications/initialized`.
+    ///
+    /// If the server rejects `initialize` the client returns to
+    /// `Uninitialized` and the handshake may be retried. If it answers with a
+    /// protocol version this client doesn't support, the client disconnects
+    /// as the spec requires and is left `Closed`.
+    pub async fn initialize(&self) -> ClientResult<()> {
+        self.transition(SessionState::Uninitialized, SessionState::Initializing)?;
+
+        match self.handshake().await {
+            Ok(()) => {
+                // A concurrent close wins over a late handshake.
+                let _ = self.transition(SessionState::Initializing, SessionState::Ready);
+                Ok(())
+            }
+            Err(e @ ClientError::UnsupportedProtocolVersion(_)) => {
+                if let Err(close_error) = self.close().await {
+                    tracing::warn!("Failed to disconnect after version mismatch: {}", close_error);
+                }
+                Err(e)
             }
+            Err(e) => {
+                let _ = self.transition(SessionState::Initializing, SessionState::Uninitialized);
+                Err(e)
+            }
+        }
+    }
+
+    /// Offers the newest supported protocol version and accepts any
+    /// supported version the server answers with.
+    async fn handshake(&self) -> ClientResult<()> {
+        let params = json!({
+            "protocolVersion": ProtocolVersion::LATEST,
+            "capabilities": self.client_capabilities(),
+            "clientInfo": self.inner.client_info
         });
 
-        let response = self.send_request("initialize", Some(params)).await?;
+        let response = self
+            .send_request_unchecked("initialize", Some(params), RequestOptions::default())
+            .await?;
+        let result: InitializeResult = serde_json::from_value(response)?;
+        if result.protocol_version.parse::<ProtocolVersion>().is_err() {
+            return Err(ClientError::UnsupportedProtocolVersion(result.protocol_version));
+        }
+        *self.inner.shared.initialize_result.write().unwrap() = Some(result);
+
+        self.send_notification("notifications/initialized", None).await
+    }
+
+    fn client_capabilities(&self) -> ClientCapabilities {
+        let shared = &self.inner.shared;
+        ClientCapabilities {
+            roots: Some(RootsCapability {
+                list_changed: Some(true),
+            }),
+            sampling: shared.sampling.read().unwrap().as_ref().map(|_| SamplingCapability {}),
+            elicitation: shared
+                .elicitation
+                .read()
+                .unwrap()
+                .as_ref()
+                .map(|_| ElicitationCapability {}),
+            ..ClientCapabilities::default()
+        }
+    }
+
+    /// Fails with `CapabilityNotSupported` unless the server advertised `capability`.
+    fn require_capability(
+        &self,
+        capability: &str,
+        supported: impl Fn(&Capabilities) -> bool,
+    ) -> ClientResult<()> {
+        self.ensure_ready()?;
+        match self.server_capabilities() {
+            Some(capabilities) if supported(&capabilities) => Ok(()),
+            _ => Err(ClientError::CapabilityNotSupported(capability.to_string())),
+        }
+    }
 
-        if let Some(server_info) = response.get("serverInfo") {
-            self.server_info = serde_json::from_value(server_info.clone()).ok();
+    fn ensure_ready(&self) -> ClientResult<()> {
+        match self.state() {
+            SessionState::Ready => Ok(()),
+            state => Err(state.error()),
         }
+    }
 
+    /// Moves from `from` to `to`, or reports why the session is not in `from`.
+    fn transition(&self, from: SessionState, to: SessionState) -> ClientResult<()> {
+        let mut state = self.inner.shared.state.lock().unwrap();
+        if *state != from {
+            // Only `initialize` starts a handshake.
+            if to == SessionState::Initializing && *state == SessionState::Initializing {
+                return Err(ClientError::InitializeInProgress);
+            }
+            return Err(state.error());
+        }
+        *state = to;
         Ok(())
     }
 
-    pub async fn list_tools(&mut self) -> ClientResult<Vec<Tool>> {
-        let response = self.send_request("tools/list", None).await?;
-        let tools: Vec<Tool> = serde_json::from_value(response.get("tools").cloned().unwrap_or(Value::Array(vec![])))?;
-        Ok(tools)
+    /// Lists every tool, following `nextCursor` across pages.
+    pub async fn list_tools(&self) -> ClientResult<Vec<Tool>> {
+        self.tools_stream().try_collect().await
     }
 
-    pub async fn list_resources(&mut self) -> ClientResult<(Vec<Resource>, Vec<ResourceTemplate>)> {
-        let response = self.send_request("resources/list", None).await?;
-        let resources: Vec<Resource> = serde_json::from_value(response.get("resources").cloned().unwrap_or(Value::Array(vec![])))?;
-        let templates: Vec<ResourceTemplate> = serde_json::from_value(response.get("resourceTemplates").cloned().unwrap_or(Value::Array(vec![])))?;
-        Ok((resources, templates))
+    pub async fn list_tools_page(&self, cursor: Option<String>) -> ClientResult<Page<Tool>> {
+        self.require_capability("tools", |c| c.tools.is_some())?;
+        let response = self.request_page("tools/list", cursor).await?;
+        parse_page(response, "tools")
     }
 
-    pub async fn list_prompts(&mut self) -> ClientResult<Vec<Prompt>> {
-        let response = self.send_request("prompts/list", None).await?;
-        let prompts: Vec<Prompt> = serde_json::from_value(response.get("prompts").cloned().unwrap_or(Value::Array(vec![])))?;
-        Ok(prompts)
+    pub fn tools_stream(&self) -> impl Stream<Item = ClientResult<Tool>> + Send + 'static {
+        self.paginate("tools/list", |client, cursor| async move {
+            client.list_tools_page(cursor).await
+        })
     }
 
-    pub async fn call_tool(&mut self, tool_name: &str, arguments: Value) -> ClientResult<ToolResult> {
+    /// Lists every resource, following `nextCursor` across pages.
+    pub async fn list_resources(&self) -> ClientResult<Vec<Resource>> {
+        self.resources_stream().try_collect().await
+    }
+
+    pub async fn list_resources_page(&self, cursor: Option<String>) -> ClientResult<Page<Resource>> {
+        self.require_capability("resources", |c| c.resources.is_some())?;
+        let response = self.request_page("resources/list", cursor).await?;
+        parse_page(response, "resources")
+    }
+
+    pub fn resources_stream(&self) -> impl Stream<Item = ClientResult<Resource>> + Send + 'static {
+        self.paginate("resources/list", |client, cursor| async move {
+            client.list_resources_page(cursor).await
+        })
+    }
+
+    /// Lists every resource template, following `nextCursor` across pages.
+    pub async fn list_resource_templates(&self) -> ClientResult<Vec<ResourceTemplate>> {
+        self.resource_templates_stream().try_collect().await
+    }
+
+    pub async fn list_resource_templates_page(
+        &self,
+        cursor: Option<String>,
+    ) -> ClientResult<Page<ResourceTemplate>> {
+        self.require_capability("resources", |c| c.resources.is_some())?;
+        let response = self.request_page("resources/templates/list", cursor).await?;
+        parse_page(response, "resourceTemplates")
+    }
+
+    pub fn resource_templates_stream(
+        &self,
+    ) -> impl Stream<Item = ClientResult<ResourceTemplate>> + Send + 'static {
+        self.paginate("resources/templates/list", |client, cursor| async move {
+            client.list_resource_templates_page(cursor).await
+        })
+    }
+
+    /// Lists every prompt, following `nextCursor` across pages.
+    pub async fn list_prompts(&self) -> ClientResult<Vec<Prompt>> {
+        self.prompts_stream().try_collect().await
+    }
+
+    pub async fn list_prompts_page(&self, cursor: Option<String>) -> ClientResult<Page<Prompt>> {
+        self.require_capability("prompts", |c| c.prompts.is_some())?;
+        let response = self.request_page("prompts/list", cursor).await?;
+        parse_page(response, "prompts")
+    }
+
+    pub fn prompts_stream(&self) -> impl Stream<Item = ClientResult<Prompt>> + Send + 'static {
+        self.paginate("prompts/list", |client, cursor| async move {
+            client.list_prompts_page(cursor).await
+        })
+    }
+
+    async fn request_page(&self, method: &str, cursor: Option<String>) -> ClientResult<Value> {
+        let params = cursor.map(|cursor| json!({ "cursor": cursor }));
+        self.send_request(method, params).await
+    }
+
+    /// Streams the items of every page returned by `fetch`, stopping after
+    /// the last page or failing once `MAX_PAGES` pages have been read.
+    fn paginate<T, F, Fut>(
+        &self,
+        method: &'static str,
+        fetch: F,
+    ) -> impl Stream<Item = ClientResult<T>> + Send + 'static
+    where
+        T: Send + 'static,
+        F: Fn(MCPClient, Option<String>) -> Fut + Send + 'static,
+        Fut: Future<Output = ClientResult<Page<T>>> + Send + 'static,
+    {
+        let client = self.clone();
+        // (cursor, pages fetched so far), or `None` once the last page is in.
+        let start = Some((None, 0));
+
+        stream::try_unfold(start, move |state| {
+            let next_page = state.map(|(cursor, pages)| (fetch(client.clone(), cursor), pages));
+            async move {
+                let Some((page, pages)) = next_page else {
+                    return Ok(None);
+                };
+                if pages >= MAX_PAGES {
+                    return Err(ClientError::PageLimitExceeded {
+                        method: method.to_string(),
+                        limit: MAX_PAGES,
+                    });
+                }
+
+                let page = page.await?;
+                let next = page.next_cursor.map(|cursor| (Some(cursor), pages + 1));
+                Ok(Some((page.items, next)))
+            }
+        })
+        .map_ok(|items| stream::iter(items.into_iter().map(Ok)))
+        .try_flatten()
+    }
+
+    pub async fn call_tool(&self, tool_name: &str, arguments: Value) -> ClientResult<ToolResult> {
+        self.call_tool_with_options(tool_name, arguments, RequestOptions::default()).await
+    }
+
+    pub async fn call_tool_with_options(
+        &self,
+        tool_name: &str,
+        arguments: Value,
+        options: RequestOptions,
+    ) -> ClientResult<ToolResult> {
+        self.require_capability("tools", |c| c.tools.is_some())?;
         let params = json!({
             "name": tool_name,
             "arguments": arguments
         });
 
-        let response = self.send_request("tools/call", Some(params)).await?;
-        let result: ToolResult = serde_json::from_value(response)?;
+        let response = self.send_request_with("tools/call", Some(params), options).await?;
+        let mut result: ToolResult = serde_json::from_value(response)?;
+        self.drop_resource_links(tool_name, &mut result.content);
         Ok(result)
     }
 
-    pub async fn read_resource(&mut self, uri: &str) -> ClientResult<ResourceContent> {
+    /// `resource_link` content only exists from 2025-06-18 on; older sessions
+    /// shouldn't see it, so it is dropped rather than passed on.
+    fn drop_resource_links(&self, tool_name: &str, content: &mut Vec<Content>) {
+        if self
+            .protocol_version()
+            .is_some_and(ProtocolVersion::supports_resource_links)
+        {
+            return;
+        }
+
+        let before = content.len();
+        content.retain(|content| !matches!(content, Content::ResourceLink { .. }));
+        if content.len() != before {
+            tracing::warn!(
+                "Dropped {} resource_link item(s) from {} result: not supported by the negotiated protocol version",
+                before - content.len(),
+                tool_name
+            );
+        }
+    }
+
+    /// Like `call_tool`, but a result with `isError: true` becomes
+    /// `ClientError::ToolExecution`.
+    pub async fn call_tool_checked(&self, tool_name: &str, arguments: Value) -> ClientResult<ToolResult> {
+        let result = self.call_tool(tool_name, arguments).await?;
+        if result.is_error() {
+            return Err(ClientError::ToolExecution {
+                tool: tool_name.to_string(),
+                result,
+            });
+        }
+        Ok(result)
+    }
+
+    /// Calls a tool with typed arguments and deserializes its `structuredContent`.
+    ///
+    /// Needs a session on 2025-06-18 or later. A result with `isError: true`
+    /// becomes `ClientError::ToolExecution`.
+    pub async fn call_tool_typed<A: Serialize, R: DeserializeOwned>(
+        &self,
+        tool_name: &str,
+        arguments: &A,
+    ) -> ClientResult<R> {
+        self.ensure_ready()?;
+        if !self
+            .protocol_version()
+            .is_some_and(ProtocolVersion::supports_structured_output)
+        {
+            return Err(ClientError::CapabilityNotSupported("structured tool output".to_string()));
+        }
+
+        let arguments = serde_json::to_value(arguments)?;
+        let result = self.call_tool_checked(tool_name, arguments).await?;
+        let structured = result
+            .structured_content
+            .ok_or_else(|| ClientError::MissingStructuredContent(tool_name.to_string()))?;
+        Ok(serde_json::from_value(structured)?)
+    }
+
+    /// Calls a tool, passing each `notifications/progress` update for the
+    /// call to `on_progress` until the result arrives.
+    pub async fn call_tool_with_progress(
+        &self,
+        tool_name: &str,
+        arguments: Value,
+        on_progress: impl Fn(ProgressNotification) + Send + Sync + 'static,
+    ) -> ClientResult<ToolResult> {
+        let options = RequestOptions::default().with_progress(on_progress);
+        self.call_tool_with_options(tool_name, arguments, options).await
+    }
+
+    pub async fn read_resource(&self, uri: &str) -> ClientResult<ResourceContent> {
+        self.require_capability("resources", |c| c.resources.is_some())?;
         let params = json!({
             "uri": uri
         });
@@ -104,7 +731,85 @@ impl MCPClient {
         Ok(content)
     }
 
-    pub async fn get_prompt(&mut self, name: &str, arguments: Option<std::collections::HashMap<String, String>>) -> ClientResult<PromptsResult> {
+    /// Expands an RFC 6570 `uri_template` with `variables` and reads the
+    /// resulting resource.
+    pub async fn read_resource_template(
+        &self,
+        uri_template: &str,
+        variables: &HashMap<String, String>,
+    ) -> ClientResult<ResourceContent> {
+        let uri = crate::uri_template::expand(uri_template, variables)?;
+        self.read_resource(&uri).await
+    }
+
+    /// Subscribes to `notifications/resources/updated` for `uri`.
+    pub async fn subscribe_resource(&self, uri: &str) -> ClientResult<ResourceSubscription> {
+        self.require_capability("resource subscriptions", |c| {
+            c.resources.as_ref().and_then(|r| r.subscribe).unwrap_or(false)
+        })?;
+
+        // Subscribe locally first so no update sent right after the reply is missed.
+        let notifications = self.subscribe_notifications();
+        self.send_request("resources/subscribe", Some(json!({ "uri": uri })))
+            .await?;
+        *self
+            .inner
+            .subscriptions
+            .lock()
+            .unwrap()
+            .entry(uri.to_string())
+            .or_default() += 1;
+
+        Ok(ResourceSubscription::new(self.clone(), uri.to_string(), notifications))
+    }
+
+    /// Releases one subscription to `uri`. The server is told to stop sending
+    /// updates once no subscriptions to it remain.
+    pub async fn unsubscribe_resource(&self, uri: &str) -> ClientResult<()> {
+        if self.release_subscription(uri) == Some(false) {
+            return Ok(());
+        }
+
+        self.send_request("resources/unsubscribe", Some(json!({ "uri": uri })))
+            .await?;
+        Ok(())
+    }
+
+    /// Like `unsubscribe_resource`, without waiting for the server, for
+    /// subscriptions that were dropped.
+    pub(crate) fn unsubscribe_resource_detached(&self, uri: &str) {
+        if self.release_subscription(uri) != Some(true) || self.state() != SessionState::Ready {
+            return;
+        }
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+
+        let client = self.clone();
+        let uri = uri.to_string();
+        runtime.spawn(async move {
+            let params = json!({ "uri": uri });
+            if let Err(e) = client.send_request("resources/unsubscribe", Some(params)).await {
+                tracing::debug!("Failed to unsubscribe from {}: {}", uri, e);
+            }
+        });
+    }
+
+    /// Drops one reference to `uri`. Returns whether it was the last one, or
+    /// `None` if the client holds no subscription to it.
+    fn release_subscription(&self, uri: &str) -> Option<bool> {
+        let mut subscriptions = self.inner.subscriptions.lock().unwrap();
+        let count = subscriptions.get_mut(uri)?;
+        if *count > 1 {
+            *count -= 1;
+            return Some(false);
+        }
+        subscriptions.remove(uri);
+        Some(true)
+    }
+
+    pub async fn get_prompt(&self, name: &str, arguments: Option<std::collections::HashMap<String, String>>) -> ClientResult<PromptsResult> {
+        self.require_capability("prompts", |c| c.prompts.is_some())?;
         let params = json!({
             "name": name,
             "arguments": arguments.unwrap_or_default()
@@ -115,8 +820,238 @@ impl MCPClient {
         Ok(result)
     }
 
-    async fn send_request(&mut self, method: &str, params: Option<Value>) -> ClientResult<Value> {
-        let request_id = self.request_id.fetch_add(1, Ordering::SeqCst);
+    /// Asks the server for completions of a prompt or resource template
+    /// argument. `context` holds arguments the user has already filled in.
+    pub async fn complete(
+        &self,
+        reference: CompletionReference,
+        argument: CompletionArgument,
+        context: Option<HashMap<String, String>>,
+    ) -> ClientResult<Completion> {
+        // The completions capability only exists from 2025-03-26 on; older
+        // servers answer completion/complete without advertising it.
+        if self
+            .protocol_version()
+            .is_some_and(|version| version >= ProtocolVersion::V2025_03_26)
+        {
+            self.require_capability("completions", |c| c.completions.is_some())?;
+        }
+
+        let mut params = json!({
+            "ref": reference,
+            "argument": argument
+        });
+        if let Some(arguments) = context.filter(|arguments| !arguments.is_empty()) {
+            params["context"] = json!({ "arguments": arguments });
+        }
+
+        let mut response = self.send_request("completion/complete", Some(params)).await?;
+        let completion = match response.get_mut("completion") {
+            Some(completion) => serde_json::from_value(completion.take())?,
+            None => Completion::default(),
+        };
+        Ok(completion)
+    }
+
+    /// Sets the minimum severity of log messages the server should send.
+    pub async fn set_log_level(&self, level: impl Into<LoggingLevel>) -> ClientResult<()> {
+        self.require_capability("logging", |c| c.logging.is_some())?;
+        self.send_request("logging/setLevel", Some(json!({ "level": level.into() })))
+            .await?;
+        Ok(())
+    }
+
+    /// Copies server log messages to `logger` in addition to `tracing`.
+    pub fn set_logger(&self, logger: Option<Arc<McpLogger>>) {
+        *self.inner.shared.logger.write().unwrap() = logger;
+    }
+
+    /// The roots currently offered to the server.
+    pub fn roots(&self) -> Vec<Root> {
+        self.inner.shared.roots.read().unwrap().clone()
+    }
+
+    /// Offers `root` to the server, replacing any root with the same URI.
+    ///
+    /// Once the session is ready, the server is sent
+    /// `notifications/roots/list_changed`.
+    pub async fn add_root(&self, root: Root) -> ClientResult<()> {
+        if !url::Url::parse(&root.uri).is_ok_and(|uri| uri.scheme() == "file") {
+            return Err(ClientError::InvalidRoot(root.uri));
+        }
+
+        {
+            let mut roots = self.inner.shared.roots.write().unwrap();
+            match roots.iter_mut().find(|existing| existing.uri == root.uri) {
+                Some(existing) => *existing = root,
+                None => roots.push(root),
+            }
+        }
+        self.roots_changed().await
+    }
+
+    /// Withdraws the root with this URI; returns whether there was one.
+    pub async fn remove_root(&self, uri: &str) -> ClientResult<bool> {
+        let removed = {
+            let mut roots = self.inner.shared.roots.write().unwrap();
+            let before = roots.len();
+            roots.retain(|root| root.uri != uri);
+            roots.len() != before
+        };
+        if removed {
+            self.roots_changed().await?;
+        }
+        Ok(removed)
+    }
+
+    async fn roots_changed(&self) -> ClientResult<()> {
+        if self.state() != SessionState::Ready {
+            return Ok(());
+        }
+        self.send_notification("notifications/roots/list_changed", None).await
+    }
+
+    /// Lets servers request LLM completions through `handler`.
+    ///
+    /// The `sampling` capability is only advertised if a handler is set
+    /// before `initialize`.
+    pub fn set_sampling_handler(&self, handler: Option<Arc<dyn SamplingHandler>>) {
+        *self.inner.shared.sampling.write().unwrap() = handler;
+    }
+
+    /// Lets servers ask the user for input through `handler`.
+    ///
+    /// The `elicitation` capability is only advertised if a handler is set
+    /// before `initialize`, and only sessions on 2025-06-18 or later use it.
+    pub fn set_elicitation_handler(&self, handler: Option<Arc<dyn ElicitationHandler>>) {
+        *self.inner.shared.elicitation.write().unwrap() = handler;
+    }
+
+    /// Sends `ping` and returns the round-trip time.
+    pub async fn ping(&self) -> ClientResult<Duration> {
+        self.ping_with_options(RequestOptions::default()).await
+    }
+
+    async fn ping_with_options(&self, options: RequestOptions) -> ClientResult<Duration> {
+        let started = Instant::now();
+        self.send_request_with("ping", None, options).await?;
+        Ok(started.elapsed())
+    }
+
+    /// Pings the server every `interval` in the background. After
+    /// `max_missed` consecutive failed pings the connection is considered
+    /// dead and the client is closed. A `max_missed` of 0 is treated as 1,
+    /// so one failed ping is always needed before closing.
+    ///
+    /// Replaces any keepalive started earlier.
+    pub fn start_keepalive(&self, interval: Duration, max_missed: u32) {
+        // A weak handle so the task doesn't keep an abandoned client alive.
+        let inner = Arc::downgrade(&self.inner);
+        let task = tokio::spawn(Self::keepalive(inner, interval, max_missed.max(1)));
+
+        if let Some(previous) = self.inner.keepalive.lock().unwrap().replace(task) {
+            previous.abort();
+        }
+    }
+
+    pub fn stop_keepalive(&self) {
+        if let Some(task) = self.inner.keepalive.lock().unwrap().take() {
+            task.abort();
+        }
+    }
+
+    async fn keepalive(inner: Weak<ClientInner>, interval: Duration, max_missed: u32) {
+        let mut missed = 0;
+
+        loop {
+            tokio::time::sleep(interval).await;
+            let Some(inner) = inner.upgrade() else {
+                return;
+            };
+            let client = MCPClient { inner };
+
+            match client.state() {
+                SessionState::Ready => {}
+                SessionState::Closing | SessionState::Closed => return,
+                _ => continue,
+            }
+
+            let options = RequestOptions::default().with_timeout(interval);
+            match client.ping_with_options(options).await {
+                Ok(_) => missed = 0,
+                Err(e) => {
+                    missed += 1;
+                    tracing::warn!("Keepalive ping failed ({}/{}): {}", missed, max_missed, e);
+                }
+            }
+
+            if missed >= max_missed {
+                tracing::warn!("Server stopped answering pings, closing connection");
+                // Detach first so close doesn't abort the task running it, and
+                // don't wait on unsubscribes a dead server won't answer.
+                client.inner.keepalive.lock().unwrap().take();
+                client.inner.subscriptions.lock().unwrap().clear();
+                if let Err(e) = client.close().await {
+                    tracing::debug!("Failed to close dead connection: {}", e);
+                }
+                return;
+            }
+        }
+    }
+
+    pub async fn send_notification(&self, method: &str, params: Option<Value>) -> ClientResult<()> {
+        let notification = JsonRpcNotification {
+            jsonrpc: "2.0".to_string(),
+            method: method.to_string(),
+            params,
+        };
+
+        self.inner.shared.transport.send_notification(notification).await?;
+        Ok(())
+    }
+
+    async fn send_request(&self, method: &str, params: Option<Value>) -> ClientResult<Value> {
+        self.send_request_with(method, params, RequestOptions::default()).await
+    }
+
+    async fn send_request_with(
+        &self,
+        method: &str,
+        params: Option<Value>,
+        options: RequestOptions,
+    ) -> ClientResult<Value> {
+        self.ensure_ready()?;
+        self.send_request_unchecked(method, params, options).await
+    }
+
+    async fn send_request_unchecked(
+        &self,
+        method: &str,
+        params: Option<Value>,
+        options: RequestOptions,
+    ) -> ClientResult<Value> {
+        if let Some(reason) = options.cancellation.as_ref().and_then(CancellationToken::reason) {
+            return Err(ClientError::Cancelled(reason));
+        }
+
+        let request_id = self.inner.request_id.fetch_add(1, Ordering::SeqCst);
+        let shared = &self.inner.shared;
+
+        let mut params = params;
+        if let Some(handler) = options.on_progress.clone() {
+            let params = params.get_or_insert_with(|| json!({}));
+            if let Some(params) = params.as_object_mut() {
+                let meta = params.entry("_meta").or_insert_with(|| json!({}));
+                meta["progressToken"] = json!(request_id);
+            }
+            let (updates, mut received) = mpsc::unbounded_channel();
+            tokio::spawn(async move {
+                while let Some(update) = received.recv().await {
+                    handler(update);
+                }
+            });
+            shared.progress.lock().unwrap().insert(request_id, updates);
+        }
 
         let request = JsonRpcRequest {
             jsonrpc: "2.0".to_string(),
@@ -125,27 +1060,903 @@ impl MCPClient {
             params,
         };
 
-        // FIXED: Now uses the From<TransportError> implementation
-        self.transport.send(request).await?;
-        let response = self.transport.receive().await?;
-        
-        if let Some(result) = response.get("result") {
-            Ok(result.clone())
-        } else if let Some(error) = response.get("error") {
-            Err(ClientError::RequestFailed(
-                error.get("message")
-                    .and_then(|m| m.as_str())
-                    .unwrap_or("Unknown error")
-                    .to_string()
-            ))
-        } else {
-            Err(ClientError::RequestFailed("No result or error in response".to_string()))
+        let (tx, mut rx) = oneshot::channel();
+        shared.pending.lock().unwrap().insert(request_id, tx);
+
+        // The spec forbids cancelling `initialize`.
+        let mut guard = PendingGuard {
+            shared: shared.clone(),
+            request_id,
+            notify_server: method != "initialize",
+            reason: None,
+        };
+
+        if let Err(e) = shared.transport.send(request).await {
+            guard.notify_server = false;
+            return Err(e.into());
         }
+
+        let started = Instant::now();
+        let timeout = self.timeout_for(method, &options);
+        let cancelled = async {
+            match &options.cancellation {
+                Some(token) => token.cancelled().await,
+                None => std::future::pending().await,
+            }
+        };
+        let expired = async {
+            match timeout {
+                Some(timeout) => tokio::time::sleep(timeout).await,
+                None => std::future::pending().await,
+            }
+        };
+
+        let reply = tokio::select! {
+            reply = &mut rx => reply,
+            reason = cancelled => {
+                guard.reason = Some(reason.clone());
+                return Err(ClientError::Cancelled(reason));
+            }
+            () = expired => {
+                guard.reason = Some("Request timed out".to_string());
+                return Err(ClientError::Timeout {
+                    method: method.to_string(),
+                    elapsed: started.elapsed(),
+                });
+            }
+        };
+
+        // The sender is dropped without a reply only when the reader stops.
+        let response = reply.map_err(|_| TransportError::Closed)?;
+        response_result(response)
     }
 
-    pub async fn close(&mut self) -> ClientResult<()> {
-        // FIXED: Now uses the From<TransportError> implementation
-        self.transport.close().await?;
+    /// Sends several requests at once and returns their results in order.
+    ///
+    /// Requests go out as one JSON-RPC batch when the negotiated protocol
+    /// version allows it (2025-03-26). Otherwise, or once the server has
+    /// rejected a batch, they are sent as individual concurrent requests.
+    ///
+    /// The requests of a rejected batch are only sent again if they are all
+    /// read-only; otherwise the unanswered ones fail with the server's error.
+    /// A batch times out after the longest timeout among its methods.
+    pub async fn batch(&self, requests: Vec<BatchRequest>) -> ClientResult<Vec<ClientResult<Value>>> {
+        self.ensure_ready()?;
+
+        let batching = self
+            .protocol_version()
+            .is_some_and(ProtocolVersion::supports_batching)
+            && !self.inner.batches_rejected.load(Ordering::SeqCst);
+        if !batching || requests.len() < 2 {
+            return Ok(self.send_individually(requests).await);
+        }
+
+        let shared = &self.inner.shared;
+        let mut guards = Vec::with_capacity(requests.len());
+        let mut replies = FuturesUnordered::new();
+        let mut messages = Vec::with_capacity(requests.len());
+
+        for (index, request) in requests.iter().enumerate() {
+            let request_id = self.inner.request_id.fetch_add(1, Ordering::SeqCst);
+            let (tx, rx) = oneshot::channel();
+            shared.pending.lock().unwrap().insert(request_id, tx);
+            guards.push(PendingGuard {
+                shared: shared.clone(),
+                request_id,
+                notify_server: true,
+                reason: None,
+            });
+            replies.push(async move { (index, rx.await) });
+            messages.push(JsonRpcRequest {
+                jsonrpc: "2.0".to_string(),
+                id: json!(request_id),
+                method: request.method.clone(),
+                params: request.params.clone(),
+            });
+        }
+
+        let mut reject_rx = shared.await_batch_rejection();
+
+        if let Err(e) = shared.transport.send_batch(messages).await {
+            guards.iter_mut().for_each(|guard| guard.notify_server = false);
+            return Err(e.into());
+        }
+
+        // The batch gets as long as its slowest method may take; no deadline
+        // if any of them has none.
+        let deadline = requests.iter().try_fold(Duration::ZERO, |longest, request| {
+            self.timeout_for(&request.method, &RequestOptions::default())
+                .map(|timeout| longest.max(timeout))
+        });
+        let started = Instant::now();
+        let expired = async {
+            match deadline {
+                Some(timeout) => tokio::time::sleep(timeout).await,
+                None => std::future::pending().await,
+            }
+        };
+        tokio::pin!(expired);
+
+        let mut results: Vec<Option<ClientResult<Value>>> = requests.iter().map(|_| None).collect();
+        let mut rejection = None;
+        // A finished oneshot receiver must not be polled again; it finishes
+        // with an error when the session ends.
+        let mut reject_done = false;
+        while !replies.is_empty() {
+            tokio::select! {
+                // Replies first: the reader hands them over before a later
+                // rejection, and one already in means it wasn't for us.
+                biased;
+                Some((index, reply)) = replies.next() => {
+                    results[index] = Some(match reply {
+                        Ok(response) => response_result(response),
+                        Err(_) => Err(TransportError::Closed.into()),
+                    });
+                }
+                rejected = &mut reject_rx, if !reject_done => {
+                    let Ok(error) = rejected else {
+                        reject_done = true;
+                        continue;
+                    };
+                    // A server that rejects a batch answers none of it, so
+                    // once any reply is in, the error was about something else.
+                    if results.iter().any(Option::is_some) {
+                        reject_rx = shared.await_batch_rejection();
+                        continue;
+                    }
+                    self.inner.batches_rejected.store(true, Ordering::SeqCst);
+                    guards.iter_mut().for_each(|guard| guard.notify_server = false);
+                    rejection = Some(error);
+                    break;
+                }
+                () = &mut expired => {
+                    guards.iter_mut().for_each(|guard| guard.reason = Some("Request timed out".to_string()));
+                    for (result, request) in results.iter_mut().zip(&requests) {
+                        result.get_or_insert_with(|| Err(ClientError::Timeout {
+                            method: request.method.clone(),
+                            elapsed: started.elapsed(),
+                        }));
+                    }
+                    break;
+                }
+            }
+        }
+        drop(replies);
+        drop(guards);
+
+        // A rejected batch is retried one request at a time, but only if
+        // running a request twice can do no harm: the id-less error can't be
+        // tied to this batch for certain.
+        if let Some(error) = rejection {
+            let unanswered: Vec<usize> = (0..results.len()).filter(|&i| results[i].is_none()).collect();
+            if unanswered
+                .iter()
+                .all(|&i| READ_ONLY_METHODS.contains(&requests[i].method.as_str()))
+            {
+                tracing::debug!("Server rejected batch ({}), sending requests individually", error);
+                let retried = self
+                    .send_individually(unanswered.iter().map(|&i| requests[i].clone()).collect())
+                    .await;
+                for (index, result) in unanswered.into_iter().zip(retried) {
+                    results[index] = Some(result);
+                }
+            } else {
+                for index in unanswered {
+                    results[index] = Some(Err(ClientError::Rpc(error.clone())));
+                }
+            }
+        }
+
+        Ok(results.into_iter().flatten().collect())
+    }
+
+    async fn send_individually(&self, requests: Vec<BatchRequest>) -> Vec<ClientResult<Value>> {
+        let calls = requests
+            .into_iter()
+            .map(|request| async move { self.send_request(&request.method, request.params).await });
+        futures::future::join_all(calls).await
+    }
+
+    /// Releases resource subscriptions and closes the connection. Closing an
+    /// already closed client is a no-op.
+    pub async fn close(&self) -> ClientResult<()> {
+        let subscriptions: Vec<String> = self
+            .inner
+            .subscriptions
+            .lock()
+            .unwrap()
+            .drain()
+            .map(|(uri, _)| uri)
+            .collect();
+        if self.state() == SessionState::Ready {
+            let unsubscribes = subscriptions.iter().map(|uri| {
+                self.send_request("resources/unsubscribe", Some(json!({ "uri": uri })))
+            });
+            // Best effort: the connection is going away either way.
+            let _ = futures::future::join_all(unsubscribes).await;
+        }
+
+        {
+            let mut state = self.inner.shared.state.lock().unwrap();
+            if matches!(*state, SessionState::Closing | SessionState::Closed) {
+                return Ok(());
+            }
+            *state = SessionState::Closing;
+        }
+
+        self.stop_keepalive();
+        let result = self.inner.shared.transport.close().await;
+        self.inner.reader.abort();
+        *self.inner.shared.state.lock().unwrap() = SessionState::Closed;
+        self.inner.shared.notifications.lock().unwrap().take();
+        // The aborted reader never gets to run its own cleanup.
+        self.inner.shared.fail_pending();
+        result?;
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+/// Cleans up after a request whose future ends before its reply arrives,
+/// whether it was dropped, timed out or cancelled.
+struct PendingGuard {
+    shared: Arc<Shared>,
+    request_id: u64,
+    notify_server: bool,
+    reason: Option<String>,
+}
+
+impl Drop for PendingGuard {
+    fn drop(&mut self) {
+        self.shared.progress.lock().unwrap().remove(&self.request_id);
+
+        // Already removed by the reader if the reply arrived; a late reply
+        // for a removed request is discarded by the reader.
+        if self.shared.pending.lock().unwrap().remove(&self.request_id).is_none() {
+            return;
+        }
+        if !self.notify_server {
+            return;
+        }
+
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+        let notification = JsonRpcNotification {
+            jsonrpc: "2.0".to_string(),
+            method: "notifications/cancelled".to_string(),
+            params: serde_json::to_value(CancelledNotification {
+                request_id: json!(self.request_id),
+                reason: self.reason.take(),
+            })
+            .ok(),
+        };
+        let transport = self.shared.transport.clone();
+        runtime.spawn(async move {
+            if let Err(e) = transport.send_notification(notification).await {
+                tracing::debug!("Failed to send cancellation: {}", e);
+            }
+        });
+    }
+}
+
+fn parse_params<T: DeserializeOwned>(request: &JsonRpcRequest) -> Result<T, JsonRpcError> {
+    let params = request.params.clone().unwrap_or(Value::Null);
+    serde_json::from_value(params).map_err(|e| {
+        JsonRpcError::new(
+            JsonRpcError::INVALID_PARAMS,
+            format!("Invalid {} params: {}", request.method, e),
+        )
+    })
+}
+
+fn response_result(response: JsonRpcResponse) -> ClientResult<Value> {
+    if let Some(result) = response.result {
+        Ok(result)
+    } else if let Some(error) = response.error {
+        Err(ClientError::Rpc(error))
+    } else {
+        Err(ClientError::RequestFailed("No result or error in response".to_string()))
+    }
+}
+
+fn parse_page<T: DeserializeOwned>(mut response: Value, key: &str) -> ClientResult<Page<T>> {
+    let items = match response.get_mut(key) {
+        Some(items) => serde_json::from_value(items.take())?,
+        None => Vec::new(),
+    };
+    let next_cursor = response
+        .get("nextCursor")
+        .and_then(|cursor| cursor.as_str())
+        .map(str::to_string);
+
+    Ok(Page { items, next_cursor })
+}
+
+impl SessionState {
+    /// The error reported when an operation needs a different state.
+    fn error(self) -> ClientError {
+        match self {
+            SessionState::Uninitialized | SessionState::Initializing => ClientError::NotInitialized,
+            SessionState::Ready => ClientError::AlreadyInitialized,
+            SessionState::Closing | SessionState::Closed => ClientError::Closed,
+        }
+    }
+}
+
+impl Shared {
+    async fn read_loop(self: Arc<Self>) {
+        loop {
+            match self.transport.receive().await {
+                Ok(Value::Array(messages)) => {
+                    for message in messages {
+                        self.dispatch(message);
+                    }
+                }
+                Ok(message) => self.dispatch(message),
+                Err(TransportError::JsonError(e)) => {
+                    tracing::warn!("Discarding malformed message from server: {}", e);
+                }
+                Err(e) => {
+                    tracing::debug!("Stopping reader: {}", e);
+                    break;
+                }
+            }
+        }
+
+        *self.state.lock().unwrap() = SessionState::Closed;
+        self.notifications.lock().unwrap().take();
+        self.fail_pending();
+    }
+
+    /// Registers for the next id-less error, which is how a server rejects
+    /// a batch as a whole.
+    fn await_batch_rejection(&self) -> oneshot::Receiver<JsonRpcError> {
+        let (tx, rx) = oneshot::channel();
+        let mut rejections = self.batch_rejections.lock().unwrap();
+        rejections.retain(|waiter| !waiter.is_closed());
+        rejections.push(tx);
+        rx
+    }
+
+    /// Fails every request still waiting for a reply by dropping its sender.
+    fn fail_pending(&self) {
+        self.pending.lock().unwrap().clear();
+        self.progress.lock().unwrap().clear();
+        self.batch_rejections.lock().unwrap().clear();
+    }
+
+    fn dispatch(self: &Arc<Self>, message: Value) {
+        if message.get("method").is_some() {
+            if message.get("id").is_some() {
+                self.dispatch_request(message);
+            } else {
+                self.dispatch_notification(message);
+            }
+            return;
+        }
+
+        let response: JsonRpcResponse = match serde_json::from_value(message) {
+            Ok(response) => response,
+            Err(e) => {
+                tracing::warn!("Discarding invalid response from server: {}", e);
+                return;
+            }
+        };
+
+        // An error without an id answers a message the server couldn't
+        // parse or accept as a whole, which for us means a batch.
+        if response.id.is_null() {
+            if let Some(error) = response.error {
+                for waiter in self.batch_rejections.lock().unwrap().drain(..) {
+                    let _ = waiter.send(error.clone());
+                }
+            }
+            return;
+        }
+
+        let sender = response
+            .id
+            .as_u64()
+            .and_then(|id| self.pending.lock().unwrap().remove(&id));
+
+        match sender {
+            Some(sender) => {
+                let _ = sender.send(response);
+            }
+            None => tracing::debug!("Discarding response for unknown request id {}", response.id),
+        }
+    }
+
+    /// Re-emits a server log message as a `tracing` event and on the
+    /// configured `McpLogger`, tagged with the server and logger names.
+    fn forward_log(&self, message: &LoggingMessageNotification) {
+        let server = self
+            .initialize_result
+            .read()
+            .unwrap()
+            .as_ref()
+            .map(|result| result.server_info.name.clone())
+            .unwrap_or_default();
+        let logger = message.logger.as_deref().unwrap_or_default();
+        let text = match &message.data {
+            Value::String(text) => text.clone(),
+            data => data.to_string(),
+        };
+
+        match message.level {
+            LoggingLevel::Debug => tracing::debug!(server, logger, "{}", text),
+            LoggingLevel::Info | LoggingLevel::Notice => tracing::info!(server, logger, "{}", text),
+            LoggingLevel::Warning => tracing::warn!(server, logger, "{}", text),
+            _ => tracing::error!(server, logger, level = ?message.level, "{}", text),
+        }
+
+        if let Some(mcp_logger) = &*self.logger.read().unwrap() {
+            let source = if logger.is_empty() {
+                server
+            } else {
+                format!("{}/{}", server, logger)
+            };
+            mcp_logger.log(message.level.into(), &format!("[{}] {}", source, text));
+        }
+    }
+
+    /// Answers a server-to-client request on a separate task, so slow
+    /// handlers don't hold up responses to our own requests.
+    fn dispatch_request(self: &Arc<Self>, message: Value) {
+        let request: JsonRpcRequest = match serde_json::from_value(message) {
+            Ok(request) => request,
+            Err(e) => {
+                tracing::warn!("Discarding invalid request from server: {}", e);
+                return;
+            }
+        };
+
+        let shared = self.clone();
+        tokio::spawn(async move {
+            let (result, error) = match shared.handle_request(&request).await {
+                Ok(result) => (Some(result), None),
+                Err(error) => (None, Some(error)),
+            };
+            let response = JsonRpcResponse {
+                jsonrpc: "2.0".to_string(),
+                id: request.id,
+                result,
+                error,
+            };
+
+            if let Err(e) = shared.transport.send_response(response).await {
+                tracing::warn!("Failed to answer {} request: {}", request.method, e);
+            }
+        });
+    }
+
+    async fn handle_request(&self, request: &JsonRpcRequest) -> Result<Value, JsonRpcError> {
+        match request.method.as_str() {
+            "ping" => Ok(json!({})),
+            "roots/list" => Ok(json!({ "roots": *self.roots.read().unwrap() })),
+            "sampling/createMessage" => {
+                let handler = self.sampling.read().unwrap().clone();
+                let Some(handler) = handler else {
+                    return Err(JsonRpcError::new(
+                        JsonRpcError::METHOD_NOT_FOUND,
+                        "Sampling is not supported by this client",
+                    ));
+                };
+                let params = parse_params(request)?;
+                let result = handler.create_message(params).await?;
+                serde_json::to_value(result)
+                    .map_err(|e| JsonRpcError::new(JsonRpcError::INTERNAL_ERROR, e.to_string()))
+            }
+            "elicitation/create" => {
+                let negotiated = self
+                    .initialize_result
+                    .read()
+                    .unwrap()
+                    .as_ref()
+                    .and_then(|result| result.protocol_version.parse::<ProtocolVersion>().ok());
+                let handler = self.elicitation.read().unwrap().clone();
+                let handler = match handler {
+                    Some(handler) if negotiated.is_some_and(ProtocolVersion::supports_elicitation) => handler,
+                    _ => {
+                        return Err(JsonRpcError::new(
+                            JsonRpcError::METHOD_NOT_FOUND,
+                            "Elicitation is not supported by this client",
+                        ));
+                    }
+                };
+                let params = parse_params(request)?;
+                let result = handler.elicit(params).await?;
+                serde_json::to_value(result)
+                    .map_err(|e| JsonRpcError::new(JsonRpcError::INTERNAL_ERROR, e.to_string()))
+            }
+            method => Err(JsonRpcError::new(
+                JsonRpcError::METHOD_NOT_FOUND,
+                format!("Method not found: {}", method),
+            )),
+        }
+    }
+
+    fn dispatch_notification(&self, message: Value) {
+        let notification: JsonRpcNotification = match serde_json::from_value(message) {
+            Ok(notification) => notification,
+            Err(e) => {
+                tracing::warn!("Discarding invalid notification from server: {}", e);
+                return;
+            }
+        };
+
+        let notification = ServerNotification::from(notification);
+        match &notification {
+            ServerNotification::Progress(progress) => {
+                let updates = progress
+                    .progress_token
+                    .as_u64()
+                    .and_then(|token| self.progress.lock().unwrap().get(&token).cloned());
+                if let Some(updates) = updates {
+                    // Fails only once the request has finished.
+                    let _ = updates.send(progress.clone());
+                }
+            }
+            ServerNotification::LoggingMessage(message) => self.forward_log(message),
+            _ => {}
+        }
+
+        // Sending only fails when nobody is subscribed, which is fine.
+        if let Some(sender) = &*self.notifications.lock().unwrap() {
+            let _ = sender.send(notification);
+        }
+    }
+}
+
+impl Drop for ClientInner {
+    fn drop(&mut self) {
+        self.reader.abort();
+        self.shared.fail_pending();
+        if let Some(task) = self.keepalive.lock().unwrap().take() {
+            task.abort();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::TransportResult;
+    use async_trait::async_trait;
+
+    /// A transport whose other end is driven by the test through `Server`.
+    struct MockTransport {
+        outgoing: mpsc::UnboundedSender<Value>,
+        incoming: tokio::sync::Mutex<mpsc::UnboundedReceiver<Value>>,
+    }
+
+    impl MockTransport {
+        fn push(&self, message: impl Serialize) -> TransportResult<()> {
+            let message = serde_json::to_value(message)?;
+            self.outgoing.send(message).map_err(|_| TransportError::Closed)
+        }
+    }
+
+    #[async_trait]
+    impl Transport for MockTransport {
+        async fn send(&self, message: JsonRpcRequest) -> TransportResult<()> {
+            self.push(message)
+        }
+
+        async fn send_notification(&self, notification: JsonRpcNotification) -> TransportResult<()> {
+            self.push(notification)
+        }
+
+        async fn send_batch(&self, requests: Vec<JsonRpcRequest>) -> TransportResult<()> {
+            self.push(requests)
+        }
+
+        async fn send_response(&self, response: JsonRpcResponse) -> TransportResult<()> {
+            self.push(response)
+        }
+
+        async fn receive(&self) -> TransportResult<Value> {
+            self.incoming.lock().await.recv().await.ok_or(TransportError::Closed)
+        }
+
+        async fn close(&self) -> TransportResult<()> {
+            Ok(())
+        }
+    }
+
+    /// The server side of a `MockTransport`.
+    struct Server {
+        to_client: mpsc::UnboundedSender<Value>,
+        from_client: mpsc::UnboundedReceiver<Value>,
+    }
+
+    impl Server {
+        /// The next message the client sent.
+        async fn next(&mut self) -> Value {
+            tokio::time::timeout(Duration::from_secs(5), self.from_client.recv())
+                .await
+                .expect("client sent nothing")
+                .expect("client transport dropped")
+        }
+
+        /// Whether the client sent anything more within a short while.
+        async fn is_idle(&mut self) -> bool {
+            tokio::time::timeout(Duration::from_millis(100), self.from_client.recv())
+                .await
+                .is_err()
+        }
+
+        fn send(&self, message: Value) {
+            self.to_client.send(message).unwrap();
+        }
+
+        fn reply(&self, request: &Value, result: Value) {
+            self.send(json!({ "jsonrpc": "2.0", "id": request["id"], "result": result }));
+        }
+
+        /// Answers a whole batch with an id-less error.
+        fn reject_batch(&self) {
+            self.send(json!({
+                "jsonrpc": "2.0",
+                "id": null,
+                "error": { "code": JsonRpcError::INVALID_REQUEST, "message": "Batches not supported" }
+            }));
+        }
+    }
+
+    /// A client that has not started the handshake yet.
+    fn mock_client() -> (MCPClient, Server) {
+        let (outgoing, from_client) = mpsc::unbounded_channel();
+        let (to_client, incoming) = mpsc::unbounded_channel();
+        let transport = MockTransport {
+            outgoing,
+            incoming: tokio::sync::Mutex::new(incoming),
+        };
+        let client = MCPClient::new(
+            Arc::new(transport),
+            ClientInfo {
+                name: "test".to_string(),
+                version: "1.0".to_string(),
+            },
+        );
+        (client, Server { to_client, from_client })
+    }
+
+    /// A client that has completed the handshake at `version`.
+    async fn connect(version: &str) -> (MCPClient, Server) {
+        let (client, mut server) = mock_client();
+        let handshake = tokio::spawn({
+            let client = client.clone();
+            async move { client.initialize().await }
+        });
+        let request = server.next().await;
+        assert_eq!(request["method"], "initialize");
+        server.reply(
+            &request,
+            json!({
+                "protocolVersion": version,
+                "capabilities": { "tools": {}, "resources": {} },
+                "serverInfo": { "name": "mock", "version": "1.0" }
+            }),
+        );
+        assert_eq!(server.next().await["method"], "notifications/initialized");
+        handshake.await.unwrap().unwrap();
+
+        (client, server)
+    }
+
+    #[tokio::test]
+    async fn closing_during_a_batch_fails_every_request() {
+        let (client, mut server) = connect("2025-03-26").await;
+        let batch = tokio::spawn({
+            let client = client.clone();
+            async move {
+                client
+                    .batch((0..4).map(|_| BatchRequest::new("tools/list", None)).collect())
+                    .await
+            }
+        });
+        assert_eq!(server.next().await.as_array().map(Vec::len), Some(4));
+
+        client.close().await.unwrap();
+
+        let results = batch.await.unwrap().unwrap();
+        assert_eq!(results.len(), 4);
+        for result in results {
+            assert!(matches!(
+                result,
+                Err(ClientError::TransportError(TransportError::Closed))
+            ));
+        }
+    }
+
+    fn tool_result(text: &str) -> Value {
+        json!({ "content": [{ "type": "text", "text": text }] })
+    }
+
+    fn spawn_call(client: &MCPClient, tool_name: &'static str) -> JoinHandle<ClientResult<ToolResult>> {
+        let client = client.clone();
+        tokio::spawn(async move { client.call_tool(tool_name, json!({})).await })
+    }
+
+    fn spawn_batch(
+        client: &MCPClient,
+        methods: &[&str],
+    ) -> JoinHandle<ClientResult<Vec<ClientResult<Value>>>> {
+        let client = client.clone();
+        let requests = methods.iter().map(|method| BatchRequest::new(method, None)).collect();
+        tokio::spawn(async move { client.batch(requests).await })
+    }
+
+    #[tokio::test]
+    async fn batch_results_come_back_in_request_order() {
+        let (client, mut server) = connect("2025-03-26").await;
+        let batch = spawn_batch(&client, &["tools/list", "resources/list"]);
+
+        let sent = server.next().await;
+        let requests = sent.as_array().unwrap();
+        assert_eq!(requests.len(), 2);
+        server.reply(&requests[1], json!({ "resources": [] }));
+        server.reply(&requests[0], json!({ "tools": [] }));
+
+        let results = batch.await.unwrap().unwrap();
+        assert_eq!(results[0].as_ref().unwrap(), &json!({ "tools": [] }));
+        assert_eq!(results[1].as_ref().unwrap(), &json!({ "resources": [] }));
+    }
+
+    #[tokio::test]
+    async fn rejected_read_only_batch_is_sent_again_individually() {
+        let (client, mut server) = connect("2025-03-26").await;
+        let batch = spawn_batch(&client, &["tools/list", "prompts/list"]);
+
+        assert!(server.next().await.is_array());
+        server.reject_batch();
+        for _ in 0..2 {
+            let request = server.next().await;
+            let result = match request["method"].as_str() {
+                Some("tools/list") => json!({ "tools": [] }),
+                Some("prompts/list") => json!({ "prompts": [] }),
+                method => panic!("unexpected request {:?}", method),
+            };
+            server.reply(&request, result);
+        }
+
+        let results = batch.await.unwrap().unwrap();
+        assert_eq!(results[0].as_ref().unwrap(), &json!({ "tools": [] }));
+        assert_eq!(results[1].as_ref().unwrap(), &json!({ "prompts": [] }));
+
+        // Later batches skip straight to individual requests.
+        let batch = spawn_batch(&client, &["tools/list", "tools/list"]);
+        for _ in 0..2 {
+            let request = server.next().await;
+            assert_eq!(request["method"], "tools/list");
+            server.reply(&request, json!({ "tools": [] }));
+        }
+        assert!(batch.await.unwrap().unwrap().iter().all(Result::is_ok));
+    }
+
+    #[tokio::test]
+    async fn rejected_batch_with_a_tool_call_is_not_sent_again() {
+        let (client, mut server) = connect("2025-03-26").await;
+        let batch = spawn_batch(&client, &["tools/list", "tools/call"]);
+
+        assert!(server.next().await.is_array());
+        server.reject_batch();
+
+        let results = batch.await.unwrap().unwrap();
+        for result in &results {
+            let error = result.as_ref().unwrap_err().rpc_error().unwrap();
+            assert_eq!(error.code, JsonRpcError::INVALID_REQUEST);
+        }
+        assert!(server.is_idle().await);
+    }
+
+    #[tokio::test]
+    async fn id_less_error_after_a_reply_does_not_reject_the_batch() {
+        let (client, mut server) = connect("2025-03-26").await;
+        let batch = spawn_batch(&client, &["tools/list", "resources/list"]);
+
+        let sent = server.next().await;
+        let requests = sent.as_array().unwrap();
+        server.reply(&requests[0], json!({ "tools": [] }));
+        server.reject_batch();
+        server.reply(&requests[1], json!({ "resources": [] }));
+
+        let results = batch.await.unwrap().unwrap();
+        assert_eq!(results[0].as_ref().unwrap(), &json!({ "tools": [] }));
+        assert_eq!(results[1].as_ref().unwrap(), &json!({ "resources": [] }));
+        assert!(server.is_idle().await);
+    }
+
+    #[tokio::test]
+    async fn replies_reach_their_callers_out_of_order() {
+        let (client, mut server) = connect("2025-06-18").await;
+        let mut notifications = client.subscribe_notifications();
+        let first = spawn_call(&client, "first");
+        let first_request = server.next().await;
+        let second = spawn_call(&client, "second");
+        let second_request = server.next().await;
+        assert_eq!(first_request["params"]["name"], "first");
+        assert_eq!(second_request["params"]["name"], "second");
+
+        server.reply(&second_request, tool_result("second"));
+        server.send(json!({ "jsonrpc": "2.0", "method": "notifications/tools/list_changed" }));
+        server.send(json!({ "jsonrpc": "2.0", "id": "server-1", "method": "ping" }));
+        server.reply(&first_request, tool_result("first"));
+
+        assert_eq!(second.await.unwrap().unwrap().text(), "second");
+        assert_eq!(first.await.unwrap().unwrap().text(), "first");
+        assert!(matches!(
+            notifications.recv().await.unwrap(),
+            ServerNotification::ToolsListChanged
+        ));
+        let pong = server.next().await;
+        assert_eq!(pong["id"], "server-1");
+        assert_eq!(pong["result"], json!({}));
+    }
+
+    #[tokio::test]
+    async fn dropping_a_call_cancels_it_on_the_server() {
+        let (client, mut server) = connect("2025-06-18").await;
+        let call = spawn_call(&client, "slow");
+        let request = server.next().await;
+
+        call.abort();
+
+        let cancelled = server.next().await;
+        assert_eq!(cancelled["method"], "notifications/cancelled");
+        assert_eq!(cancelled["params"]["requestId"], request["id"]);
+    }
+
+    #[tokio::test]
+    async fn cancellation_token_ends_the_call_and_discards_its_late_reply() {
+        let (client, mut server) = connect("2025-06-18").await;
+        let token = CancellationToken::new();
+        let call = tokio::spawn({
+            let client = client.clone();
+            let options = RequestOptions::default().with_cancellation(token.clone());
+            async move { client.call_tool_with_options("slow", json!({}), options).await }
+        });
+        let request = server.next().await;
+
+        token.cancel("User pressed stop");
+
+        assert!(matches!(
+            call.await.unwrap(),
+            Err(ClientError::Cancelled(reason)) if reason == "User pressed stop"
+        ));
+        let cancelled = server.next().await;
+        assert_eq!(cancelled["params"]["requestId"], request["id"]);
+        assert_eq!(cancelled["params"]["reason"], "User pressed stop");
+
+        let next = spawn_call(&client, "next");
+        let next_request = server.next().await;
+        server.reply(&request, tool_result("late"));
+        server.reply(&next_request, tool_result("next"));
+        assert_eq!(next.await.unwrap().unwrap().text(), "next");
+    }
+
+    #[tokio::test]
+    async fn initialize_reports_a_handshake_in_progress() {
+        let (client, mut server) = mock_client();
+        let handshake = tokio::spawn({
+            let client = client.clone();
+            async move { client.initialize().await }
+        });
+        let request = server.next().await;
+
+        assert!(matches!(client.initialize().await, Err(ClientError::InitializeInProgress)));
+        assert!(matches!(client.list_tools().await, Err(ClientError::NotInitialized)));
+
+        server.reply(
+            &request,
+            json!({
+                "protocolVersion": "2025-06-18",
+                "capabilities": {},
+                "serverInfo": { "name": "mock", "version": "1.0" }
+            }),
+        );
+        handshake.await.unwrap().unwrap();
+        assert!(matches!(client.initialize().await, Err(ClientError::AlreadyInitialized)));
+    }
+}
@@ -0,0 +1,330 @@
+//! Answering `elicitation/create` requests, in which a server asks the user
+//! for structured input in the middle of an operation.
+
+use crate::types::{ElicitRequest, ElicitResult, ElicitationProperty, JsonRpcError};
+use async_trait::async_trait;
+use serde_json::{Map, Number, Value};
+use std::io;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::sync::Mutex;
+
+/// Collects the input a server asks for.
+///
+/// Register one with `MCPClient::set_elicitation_handler` before
+/// `initialize` so the client advertises the `elicitation` capability.
+/// Servers only send these requests from protocol version 2025-06-18 on.
+#[async_trait]
+pub trait ElicitationHandler: Send + Sync {
+    async fn elicit(&self, request: ElicitRequest) -> Result<ElicitResult, JsonRpcError>;
+}
+
+type Input = Box<dyn AsyncBufRead + Unpin + Send>;
+type Output = Box<dyn AsyncWrite + Unpin + Send>;
+
+/// Asks on the terminal, one prompt per schema field.
+///
+/// The user first chooses to respond, decline or cancel. Answers are checked
+/// against each field's type and constraints and asked again until they
+/// fit; end of input cancels. Concurrent requests wait their turn.
+pub struct TerminalElicitor {
+    io: Mutex<(Input, Output)>,
+}
+
+impl TerminalElicitor {
+    /// Prompts on stdout and reads stdin.
+    pub fn new() -> Self {
+        Self::with_io(BufReader::new(tokio::io::stdin()), tokio::io::stdout())
+    }
+
+    pub fn with_io(
+        input: impl AsyncBufRead + Unpin + Send + 'static,
+        output: impl AsyncWrite + Unpin + Send + 'static,
+    ) -> Self {
+        TerminalElicitor {
+            io: Mutex::new((Box::new(input), Box::new(output))),
+        }
+    }
+}
+
+impl Default for TerminalElicitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl ElicitationHandler for TerminalElicitor {
+    async fn elicit(&self, request: ElicitRequest) -> Result<ElicitResult, JsonRpcError> {
+        let mut io = self.io.lock().await;
+        let (input, output) = &mut *io;
+        run(input, output, request).await.map_err(|e| {
+            JsonRpcError::new(JsonRpcError::INTERNAL_ERROR, format!("Terminal I/O failed: {}", e))
+        })
+    }
+}
+
+async fn run(input: &mut Input, output: &mut Output, request: ElicitRequest) -> io::Result<ElicitResult> {
+    output.write_all(format!("\n{}\n", request.message).as_bytes()).await?;
+
+    loop {
+        let Some(answer) = ask(input, output, "Respond? [Y]es / [n]o / [c]ancel: ").await? else {
+            return Ok(ElicitResult::cancel());
+        };
+        match answer.to_lowercase().as_str() {
+            "" | "y" | "yes" => break,
+            "n" | "no" => return Ok(ElicitResult::decline()),
+            "c" | "cancel" => return Ok(ElicitResult::cancel()),
+            _ => {}
+        }
+    }
+
+    let schema = request.requested_schema;
+    let mut content = Map::new();
+    for (name, property) in &schema.properties {
+        let required = schema.required.contains(name);
+        let label = field_label(name, property, required);
+        loop {
+            let Some(answer) = ask(input, output, &label).await? else {
+                return Ok(ElicitResult::cancel());
+            };
+
+            if answer.is_empty() {
+                if let Some(default) = &property.default {
+                    content.insert(name.clone(), default.clone());
+                    break;
+                }
+                if !required {
+                    break;
+                }
+                output.write_all(b"A value is required.\n").await?;
+                continue;
+            }
+
+            match parse_value(property, &answer) {
+                Ok(value) => {
+                    content.insert(name.clone(), value);
+                    break;
+                }
+                Err(problem) => output.write_all(format!("{}\n", problem).as_bytes()).await?,
+            }
+        }
+    }
+
+    Ok(ElicitResult::accept(content))
+}
+
+/// Prints `prompt` and reads one trimmed line; `None` at end of input.
+async fn ask(input: &mut Input, output: &mut Output, prompt: &str) -> io::Result<Option<String>> {
+    output.write_all(prompt.as_bytes()).await?;
+    output.flush().await?;
+
+    let mut line = String::new();
+    if input.read_line(&mut line).await? == 0 {
+        return Ok(None);
+    }
+    Ok(Some(line.trim().to_string()))
+}
+
+fn field_label(name: &str, property: &ElicitationProperty, required: bool) -> String {
+    let mut label = property.title.clone().unwrap_or_else(|| name.to_string());
+    if let Some(description) = &property.description {
+        label.push_str(&format!(" ({})", description));
+    }
+
+    if let Some(values) = &property.enum_values {
+        let choices: Vec<&str> = match &property.enum_names {
+            Some(names) if names.len() == values.len() => names.iter().map(String::as_str).collect(),
+            _ => values.iter().map(String::as_str).collect(),
+        };
+        label.push_str(&format!(" [{}]", choices.join(" / ")));
+    } else if property.property_type == "boolean" {
+        label.push_str(" [y/n]");
+    }
+
+    if let Some(default) = &property.default {
+        label.push_str(&format!(" [default: {}]", default));
+    } else if !required {
+        label.push_str(" [optional]");
+    }
+    label.push_str(": ");
+    label
+}
+
+/// Converts an answer to the property's JSON type, or explains why it doesn't fit.
+fn parse_value(property: &ElicitationProperty, answer: &str) -> Result<Value, String> {
+    if let Some(values) = &property.enum_values {
+        let names = property.enum_names.as_deref().unwrap_or_default();
+        return values
+            .iter()
+            .enumerate()
+            .find(|(i, value)| {
+                value.eq_ignore_ascii_case(answer)
+                    || names.get(*i).is_some_and(|name| name.eq_ignore_ascii_case(answer))
+            })
+            .map(|(_, value)| Value::String(value.clone()))
+            .ok_or_else(|| format!("Choose one of: {}", values.join(", ")));
+    }
+
+    match property.property_type.as_str() {
+        "boolean" => match answer.to_lowercase().as_str() {
+            "y" | "yes" | "true" => Ok(Value::Bool(true)),
+            "n" | "no" | "false" => Ok(Value::Bool(false)),
+            _ => Err("Answer y or n.".to_string()),
+        },
+        "integer" => {
+            let number: i64 = answer.parse().map_err(|_| "Enter a whole number.".to_string())?;
+            check_range(property, number as f64)?;
+            Ok(Value::from(number))
+        }
+        "number" => {
+            let number = answer
+                .parse::<f64>()
+                .ok()
+                .and_then(Number::from_f64)
+                .ok_or_else(|| "Enter a number.".to_string())?;
+            check_range(property, number.as_f64().unwrap_or_default())?;
+            Ok(Value::Number(number))
+        }
+        _ => {
+            let length = answer.chars().count();
+            if property.min_length.is_some_and(|min| length < min) {
+                return Err(format!("Enter at least {} characters.", property.min_length.unwrap_or_default()));
+            }
+            if property.max_length.is_some_and(|max| length > max) {
+                return Err(format!("Enter at most {} characters.", property.max_length.unwrap_or_default()));
+            }
+            check_format(property.format.as_deref(), answer)?;
+            Ok(Value::String(answer.to_string()))
+        }
+    }
+}
+
+fn check_range(property: &ElicitationProperty, number: f64) -> Result<(), String> {
+    if let Some(minimum) = property.minimum.filter(|&minimum| number < minimum) {
+        return Err(format!("Enter a number of at least {}.", minimum));
+    }
+    if let Some(maximum) = property.maximum.filter(|&maximum| number > maximum) {
+        return Err(format!("Enter a number of at most {}.", maximum));
+    }
+    Ok(())
+}
+
+fn check_format(format: Option<&str>, answer: &str) -> Result<(), String> {
+    let valid = match format {
+        Some("email") => answer
+            .split_once('@')
+            .is_some_and(|(user, domain)| !user.is_empty() && domain.contains('.')),
+        Some("uri") => url::Url::parse(answer).is_ok(),
+        Some("date") => chrono::NaiveDate::parse_from_str(answer, "%Y-%m-%d").is_ok(),
+        Some("date-time") => chrono::DateTime::parse_from_rfc3339(answer).is_ok(),
+        _ => true,
+    };
+
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("Enter a valid {}.", format.unwrap_or_default()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ElicitAction;
+    use serde_json::json;
+    use tokio::io::AsyncReadExt;
+
+    /// Answers `schema` with the scripted `input` and returns the result
+    /// together with everything printed.
+    async fn elicit(schema: Value, input: &'static str) -> (ElicitResult, String) {
+        let request = serde_json::from_value(json!({
+            "message": "Tell us about yourself",
+            "requestedSchema": schema
+        }))
+        .unwrap();
+        let (output, mut printed) = tokio::io::duplex(1 << 16);
+        let elicitor = TerminalElicitor::with_io(input.as_bytes(), output);
+        let result = elicitor.elicit(request).await.unwrap();
+        drop(elicitor);
+
+        let mut text = String::new();
+        printed.read_to_string(&mut text).await.unwrap();
+        (result, text)
+    }
+
+    fn name_schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": { "name": { "type": "string" } },
+            "required": ["name"]
+        })
+    }
+
+    #[tokio::test]
+    async fn accepts_the_answers() {
+        let (result, _) = elicit(name_schema(), "y\nAda\n").await;
+        assert_eq!(result.action, ElicitAction::Accept);
+        assert_eq!(result.content.unwrap()["name"], "Ada");
+    }
+
+    #[tokio::test]
+    async fn declines_and_cancels() {
+        let (result, _) = elicit(name_schema(), "n\n").await;
+        assert_eq!(result.action, ElicitAction::Decline);
+        assert!(result.content.is_none());
+
+        let (result, _) = elicit(name_schema(), "cancel\n").await;
+        assert_eq!(result.action, ElicitAction::Cancel);
+    }
+
+    #[tokio::test]
+    async fn end_of_input_cancels() {
+        let (result, _) = elicit(name_schema(), "").await;
+        assert_eq!(result.action, ElicitAction::Cancel);
+
+        let (result, _) = elicit(name_schema(), "y\n").await;
+        assert_eq!(result.action, ElicitAction::Cancel);
+    }
+
+    #[tokio::test]
+    async fn enum_can_be_chosen_by_its_display_name() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "size": { "type": "string", "enum": ["s", "l"], "enumNames": ["Small", "Large"] }
+            }
+        });
+        let (result, printed) = elicit(schema, "y\nlarge\n").await;
+        assert_eq!(result.content.unwrap()["size"], "l");
+        assert!(printed.contains("[Small / Large]"));
+    }
+
+    #[tokio::test]
+    async fn out_of_range_integer_is_asked_again() {
+        let schema = json!({
+            "type": "object",
+            "properties": { "age": { "type": "integer", "minimum": 0, "maximum": 150 } }
+        });
+        let (result, printed) = elicit(schema, "y\n200\n42\n").await;
+        assert_eq!(result.content.unwrap()["age"], 42);
+        assert!(printed.contains("Enter a number of at most 150."));
+    }
+
+    #[tokio::test]
+    async fn required_field_left_empty_is_asked_again() {
+        let (result, printed) = elicit(name_schema(), "y\n\nAda\n").await;
+        assert_eq!(result.content.unwrap()["name"], "Ada");
+        assert!(printed.contains("A value is required."));
+    }
+
+    #[tokio::test]
+    async fn empty_answer_takes_the_default() {
+        let schema = json!({
+            "type": "object",
+            "properties": { "subscribe": { "type": "boolean", "default": true } },
+            "required": ["subscribe"]
+        });
+        let (result, _) = elicit(schema, "y\n\n").await;
+        assert_eq!(result.content.unwrap()["subscribe"], true);
+    }
+}
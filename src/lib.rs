@@ -1,7 +1,12 @@
 pub mod types;
 pub mod transport;
 pub mod client;
+pub mod elicitation;
+pub mod notifications;
+pub mod subscription;
+pub mod uri_template;
 pub mod ollama;
+pub mod sampling;
 pub mod tool_manager;
 pub mod multi_server;
 pub mod security;
@@ -10,7 +15,11 @@ pub mod validation;
 pub mod errors;
 
 // Re-export commonly used types
-pub use client::{MCPClient, ClientError, ClientResult};
+pub use client::{MCPClient, ClientError, ClientResult, SessionState, RequestOptions, CancellationToken, ProgressHandler, BatchRequest};
+pub use elicitation::{ElicitationHandler, TerminalElicitor};
 pub use errors::{McpError, McpResult};
+pub use notifications::ServerNotification;
+pub use sampling::{SamplingHandler, OllamaSampler};
+pub use subscription::ResourceSubscription;
 pub use transport::Transport;
 pub use types::*;
\ No newline at end of file
@@ -1,3 +1,4 @@
+use crate::types::LoggingLevel;
 use chrono::Local;
 use std::fs::OpenOptions;
 use std::io::Write;
@@ -10,6 +11,28 @@ pub enum LogLevel {
     Error = 3,
 }
 
+impl From<LogLevel> for LoggingLevel {
+    fn from(level: LogLevel) -> Self {
+        match level {
+            LogLevel::Debug => LoggingLevel::Debug,
+            LogLevel::Info => LoggingLevel::Info,
+            LogLevel::Warn => LoggingLevel::Warning,
+            LogLevel::Error => LoggingLevel::Error,
+        }
+    }
+}
+
+impl From<LoggingLevel> for LogLevel {
+    fn from(level: LoggingLevel) -> Self {
+        match level {
+            LoggingLevel::Debug => LogLevel::Debug,
+            LoggingLevel::Info | LoggingLevel::Notice => LogLevel::Info,
+            LoggingLevel::Warning => LogLevel::Warn,
+            _ => LogLevel::Error,
+        }
+    }
+}
+
 pub struct McpLogger {
     level: LogLevel,
     log_file: Option<String>,
@@ -44,7 +67,7 @@ impl McpLogger {
         self.log(LogLevel::Error, message);
     }
 
-    fn log(&self, level: LogLevel, message: &str) {
+    pub fn log(&self, level: LogLevel, message: &str) {
         if level >= self.level {
             let timestamp = Local::now().format("%Y-%m-%d %H:%M:%S%.3f");
             let level_str = match level {
@@ -58,14 +81,13 @@ impl McpLogger {
 
             println!("{}", log_message);
 
-            if let Some(ref log_file) = self.log_file {
-                if let Ok(mut file) = OpenOptions::new()
+            if let Some(ref log_file) = self.log_file
+                && let Ok(mut file) = OpenOptions::new()
                     .create(true)
                     .append(true)
                     .open(log_file)
-                {
-                    let _ = writeln!(file, "{}", log_message);
-                }
+            {
+                let _ = writeln!(file, "{}", log_message);
             }
         }
     }
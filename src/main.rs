@@ -20,7 +20,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         version: "1.0.0".to_string(),
     };
 
-    let mut client = MCPClient::new(transport, client_info);
+    let client = MCPClient::new(transport, client_info);
     client.initialize().await?;
     println!("✓ Connected to server");
 
@@ -33,23 +33,18 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Call greeting tool
     println!("\nCalling greeting tool:");
-    let result = client.call_tool(
+    let result = client.call_tool_checked(
         "greet",
         json!({
             "name": "Alice"
         })
     ).await?;
     
-    match &result.content[0] {
-        mcp_client_rust::types::ToolResultContent::Text { text } => {
-            println!("Greeting result: {}", text);
-        }
-        _ => println!("Received non-text result"),
-    }
+    println!("Greeting result: {}", result.text());
 
     // Calculate BMI
     println!("\nCalculating BMI:");
-    let bmi_result = client.call_tool(
+    let bmi_result = client.call_tool_checked(
         "calculate-bmi",
         json!({
             "weightKg": 70.0,
@@ -57,28 +52,18 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         })
     ).await?;
     
-    match &bmi_result.content[0] {
-        mcp_client_rust::types::ToolResultContent::Text { text } => {
-            println!("BMI result: {}", text);
-        }
-        _ => println!("Received non-text result"),
-    }
+    println!("BMI result: {}", bmi_result.text());
 
     // Fetch weather
     println!("\nFetching weather:");
-    let weather_result = client.call_tool(
+    let weather_result = client.call_tool_checked(
         "fetch-weather",
         json!({
             "city": "San Francisco"
         })
     ).await?;
     
-    match &weather_result.content[0] {
-        mcp_client_rust::types::ToolResultContent::Text { text } => {
-            println!("Weather result: {}", text);
-        }
-        _ => println!("Received non-text result"),
-    }
+    println!("Weather result: {}", weather_result.text());
 
     // Read configuration resource
     println!("\nReading configuration resource:");
@@ -86,10 +71,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     
     for item in config_content.contents {
         match item {
-            mcp_client_rust::types::ContentItem::Text { text } => {
+            mcp_client_rust::types::ResourceContents::Text { text, .. } => {
                 println!("Config content: {}", text);
             }
-            mcp_client_rust::types::ContentItem::Blob { blob } => {
+            mcp_client_rust::types::ResourceContents::Blob { blob, .. } => {
                 println!("Binary config data: {} bytes", blob.len());
             }
         }
@@ -107,15 +92,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     
     for message in prompt_result.messages {
         println!("Role: {}", message.role);
-        for content in message.content {
-            match content {
-                mcp_client_rust::types::MessageContent::Text { text } => {
-                    println!("Prompt message: {}", text);
-                }
-                mcp_client_rust::types::MessageContent::Blob { blob } => {
-                    println!("Binary prompt data: {} bytes", blob.len());
-                }
-            }
+        match message.content.as_text() {
+            Some(text) => println!("Prompt message: {}", text),
+            None => println!("Prompt content: {:?}", message.content.mime_type()),
         }
     }
 
@@ -37,7 +37,7 @@ impl MultiServerManager {
             version: "1.0.0".to_string(),
         };
 
-        let mut client = MCPClient::new(transport, client_info);
+        let client = MCPClient::new(transport, client_info);
         client.initialize().await?;
 
         self.server_configs.insert(config.id.clone(), config.clone());
@@ -47,33 +47,31 @@ impl MultiServerManager {
     }
 
     pub async fn remove_server(&mut self, id: &str) -> McpResult<()> {
-        if let Some(mut client) = self.servers.remove(id) {
+        if let Some(client) = self.servers.remove(id) {
             client.close().await?;
         }
         self.server_configs.remove(id);
         Ok(())
     }
 
-    pub fn get_client(&mut self, id: &str) -> Option<&mut MCPClient> {
-        self.servers.get_mut(id)
+    /// Returns a handle to the server's client; clones share the same connection.
+    pub fn get_client(&self, id: &str) -> Option<MCPClient> {
+        self.servers.get(id).cloned()
     }
 
-    pub async fn get_all_tools(&mut self) -> McpResult<Vec<(String, Tool)>> {
+    pub async fn get_all_tools(&self) -> McpResult<Vec<(String, Tool)>> {
         let mut all_tools = Vec::new();
-        let server_ids: Vec<_> = self.servers.keys().cloned().collect();
 
-        for server_id in server_ids {
-            if let Some(client) = self.servers.get_mut(&server_id) {
-                match client.list_tools().await {
-                    Ok(tools) => {
-                        for tool in tools {
-                            all_tools.push((server_id.clone(), tool));
-                        }
-                    }
-                    Err(e) => {
-                        eprintln!("Error getting tools from server {}: {}", server_id, e);
+        for (server_id, client) in &self.servers {
+            match client.list_tools().await {
+                Ok(tools) => {
+                    for tool in tools {
+                        all_tools.push((server_id.clone(), tool));
                     }
                 }
+                Err(e) => {
+                    eprintln!("Error getting tools from server {}: {}", server_id, e);
+                }
             }
         }
 
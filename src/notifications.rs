@@ -0,0 +1,56 @@
+use crate::types::*;
+use serde::de::DeserializeOwned;
+
+/// A notification sent by the server, decoded by method.
+///
+/// Notifications with an unknown method, or whose params don't match the
+/// expected shape, are passed through unchanged as `Other`.
+#[derive(Debug, Clone)]
+pub enum ServerNotification {
+    ToolsListChanged,
+    ResourcesListChanged,
+    ResourceUpdated(ResourceUpdatedNotification),
+    PromptsListChanged,
+    Progress(ProgressNotification),
+    LoggingMessage(LoggingMessageNotification),
+    Cancelled(CancelledNotification),
+    Other(JsonRpcNotification),
+}
+
+impl ServerNotification {
+    pub fn method(&self) -> &str {
+        match self {
+            ServerNotification::ToolsListChanged => "notifications/tools/list_changed",
+            ServerNotification::ResourcesListChanged => "notifications/resources/list_changed",
+            ServerNotification::ResourceUpdated(_) => "notifications/resources/updated",
+            ServerNotification::PromptsListChanged => "notifications/prompts/list_changed",
+            ServerNotification::Progress(_) => "notifications/progress",
+            ServerNotification::LoggingMessage(_) => "notifications/message",
+            ServerNotification::Cancelled(_) => "notifications/cancelled",
+            ServerNotification::Other(notification) => &notification.method,
+        }
+    }
+}
+
+impl From<JsonRpcNotification> for ServerNotification {
+    fn from(notification: JsonRpcNotification) -> Self {
+        let decoded = match notification.method.as_str() {
+            "notifications/tools/list_changed" => Some(ServerNotification::ToolsListChanged),
+            "notifications/resources/list_changed" => Some(ServerNotification::ResourcesListChanged),
+            "notifications/prompts/list_changed" => Some(ServerNotification::PromptsListChanged),
+            "notifications/resources/updated" => {
+                params(&notification).map(ServerNotification::ResourceUpdated)
+            }
+            "notifications/progress" => params(&notification).map(ServerNotification::Progress),
+            "notifications/message" => params(&notification).map(ServerNotification::LoggingMessage),
+            "notifications/cancelled" => params(&notification).map(ServerNotification::Cancelled),
+            _ => None,
+        };
+
+        decoded.unwrap_or(ServerNotification::Other(notification))
+    }
+}
+
+fn params<T: DeserializeOwned>(notification: &JsonRpcNotification) -> Option<T> {
+    serde_json::from_value(notification.params.clone()?).ok()
+}
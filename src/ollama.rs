@@ -12,10 +12,9 @@ pub struct OllamaRequest {
     pub model: String,
     pub messages: Vec<OllamaMessage>,
     pub stream: Option<bool>,
-    pub temperature: Option<f32>,
-    pub top_p: Option<f32>,
-    pub top_k: Option<i32>,
-    pub repeat_penalty: Option<f32>,
+    /// Generation parameters; Ollama ignores them anywhere but here.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub options: Option<OllamaOptions>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -66,6 +65,19 @@ impl OllamaClient {
         }
     }
 
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    /// A client for the same server that talks to a different model.
+    pub fn with_model(&self, model: &str) -> Self {
+        OllamaClient {
+            base_url: self.base_url.clone(),
+            model: model.to_string(),
+            client: self.client.clone(),
+        }
+    }
+
     pub async fn send_message(
         &self,
         messages: Vec<OllamaMessage>,
@@ -77,15 +89,12 @@ impl OllamaClient {
             model: self.model.clone(),
             messages,
             stream: Some(false),
-            temperature: opts.temperature,
-            top_p: opts.top_p,
-            top_k: opts.top_k,
-            repeat_penalty: opts.repeat_penalty,
+            options: Some(opts),
         };
 
         let response = self
             .client
-            .post(&format!("{}/api/chat", self.base_url))
+            .post(format!("{}/api/chat", self.base_url))
             .json(&request)
             .send()
             .await
@@ -102,7 +111,7 @@ impl OllamaClient {
     pub async fn list_models(&self) -> McpResult<Vec<String>> {
         let response = self
             .client
-            .get(&format!("{}/api/tags", self.base_url))
+            .get(format!("{}/api/tags", self.base_url))
             .send()
             .await
             .map_err(|e| McpError::LlmError(format!("Failed to list models: {}", e)))?;
@@ -128,7 +137,7 @@ impl OllamaClient {
     pub async fn health_check(&self) -> McpResult<bool> {
         match self
             .client
-            .get(&format!("{}/api/tags", self.base_url))
+            .get(format!("{}/api/tags", self.base_url))
             .send()
             .await
         {
@@ -138,12 +147,19 @@ impl OllamaClient {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OllamaOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub top_k: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub repeat_penalty: Option<f32>,
+    /// Maximum number of tokens to generate.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub num_predict: Option<i32>,
 }
 
 impl Default for OllamaOptions {
@@ -153,6 +169,7 @@ impl Default for OllamaOptions {
             top_p: Some(0.9),
             top_k: Some(40),
             repeat_penalty: Some(1.1),
+            num_predict: None,
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,138 @@
+//! Answering `sampling/createMessage` requests, which let a server borrow
+//! the client's LLM.
+
+use crate::ollama::{OllamaClient, OllamaMessage, OllamaOptions};
+use crate::types::{
+    Content, CreateMessageRequest, CreateMessageResult, JsonRpcError, ModelPreferences,
+};
+use async_trait::async_trait;
+use tokio::sync::OnceCell;
+
+/// Runs completions on behalf of servers.
+///
+/// Register one with `MCPClient::set_sampling_handler` before `initialize` so
+/// the client advertises the `sampling` capability. An error is sent back to
+/// the server as-is; use code -1 when the user declined the request.
+#[async_trait]
+pub trait SamplingHandler: Send + Sync {
+    async fn create_message(
+        &self,
+        request: CreateMessageRequest,
+    ) -> Result<CreateMessageResult, JsonRpcError>;
+}
+
+/// Samples through an Ollama server.
+///
+/// Model hints are matched as substrings against the installed models, in
+/// the server's order, falling back to the client's own model. The cost,
+/// speed and intelligence priorities are not used.
+pub struct OllamaSampler {
+    client: OllamaClient,
+    options: OllamaOptions,
+    models: OnceCell<Vec<String>>,
+}
+
+impl OllamaSampler {
+    pub fn new(client: OllamaClient) -> Self {
+        OllamaSampler {
+            client,
+            options: OllamaOptions::default(),
+            models: OnceCell::new(),
+        }
+    }
+
+    /// Options used for every request; `temperature` and `num_predict` are
+    /// overridden by the server's request.
+    pub fn with_options(mut self, options: OllamaOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    async fn choose_model(&self, preferences: Option<&ModelPreferences>) -> Option<String> {
+        let hints: Vec<&str> = preferences
+            .into_iter()
+            .flat_map(|preferences| &preferences.hints)
+            .filter_map(|hint| hint.name.as_deref())
+            .collect();
+        if hints.is_empty() {
+            return None;
+        }
+
+        let models = match self
+            .models
+            .get_or_try_init(|| self.client.list_models())
+            .await
+        {
+            Ok(models) => models,
+            Err(e) => {
+                tracing::warn!("Could not list Ollama models for model hints: {}", e);
+                return None;
+            }
+        };
+
+        hints
+            .iter()
+            .find_map(|hint| models.iter().find(|model| model.contains(hint)).cloned())
+    }
+}
+
+#[async_trait]
+impl SamplingHandler for OllamaSampler {
+    async fn create_message(
+        &self,
+        request: CreateMessageRequest,
+    ) -> Result<CreateMessageResult, JsonRpcError> {
+        let mut messages = Vec::with_capacity(request.messages.len() + 1);
+        if let Some(system_prompt) = request.system_prompt.filter(|prompt| !prompt.is_empty()) {
+            messages.push(OllamaMessage {
+                role: "system".to_string(),
+                content: system_prompt,
+            });
+        }
+        for message in request.messages {
+            match message.content {
+                Content::Text { text, .. } => messages.push(OllamaMessage {
+                    role: message.role,
+                    content: text,
+                }),
+                _ => {
+                    return Err(JsonRpcError::new(
+                        JsonRpcError::INVALID_PARAMS,
+                        "Only text content can be sampled with Ollama",
+                    ));
+                }
+            }
+        }
+
+        let mut options = self.options.clone();
+        options.num_predict = Some(i32::try_from(request.max_tokens).unwrap_or(i32::MAX));
+        if request.temperature.is_some() {
+            options.temperature = request.temperature;
+        }
+
+        let hinted;
+        let client = match self.choose_model(request.model_preferences.as_ref()).await {
+            Some(model) => {
+                hinted = self.client.with_model(&model);
+                &hinted
+            }
+            None => &self.client,
+        };
+
+        let response = client
+            .send_message(messages, Some(options))
+            .await
+            .map_err(|e| JsonRpcError::new(JsonRpcError::INTERNAL_ERROR, e.to_string()))?;
+
+        let hit_limit = response
+            .eval_count
+            .is_some_and(|count| count as i64 >= request.max_tokens as i64);
+
+        Ok(CreateMessageResult {
+            role: "assistant".to_string(),
+            content: Content::text(response.message.content),
+            model: response.model,
+            stop_reason: Some(if hit_limit { "maxTokens" } else { "endTurn" }.to_string()),
+        })
+    }
+}
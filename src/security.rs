@@ -1,47 +1,57 @@
+use crate::types::{Tool, ToolAnnotations};
 use std::collections::HashMap;
 use std::time::{Duration, Instant};
 
-#[derive(Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ToolPolicy {
     pub requires_approval: bool,
     pub max_calls_per_minute: usize,
 }
 
-pub struct SecurityManager {
-    tool_policies: HashMap<String, ToolPolicy>,
-    call_history: HashMap<String, Vec<Instant>>,
-}
+impl ToolPolicy {
+    /// The default policy for a tool with these annotations.
+    ///
+    /// Read-only tools run freely at a higher rate; destructive tools, which
+    /// includes any tool without annotations, need approval and are limited
+    /// the most. Other tools need approval only if they reach the open world.
+    pub fn from_annotations(annotations: Option<&ToolAnnotations>) -> Self {
+        let annotations = annotations.cloned().unwrap_or_default();
 
-impl SecurityManager {
-    pub fn new() -> Self {
-        let mut policies = HashMap::new();
-
-        policies.insert(
-            "file-write".to_string(),
+        if annotations.is_read_only() {
+            ToolPolicy {
+                requires_approval: false,
+                max_calls_per_minute: 30,
+            }
+        } else if annotations.is_destructive() {
             ToolPolicy {
                 requires_approval: true,
                 max_calls_per_minute: 5,
-            },
-        );
-
-        policies.insert(
-            "execute-command".to_string(),
+            }
+        } else {
             ToolPolicy {
-                requires_approval: true,
-                max_calls_per_minute: 2,
-            },
-        );
+                requires_approval: annotations.is_open_world(),
+                max_calls_per_minute: 10,
+            }
+        }
+    }
+}
 
-        policies.insert(
-            "read-data".to_string(),
-            ToolPolicy {
-                requires_approval: false,
-                max_calls_per_minute: 20,
-            },
-        );
+/// Per-tool approval and rate-limit policies.
+///
+/// Policies set with `set_tool_policy` take precedence over those derived
+/// from tool annotations by `register_tools`. Annotations come from the
+/// server, so set explicit policies for servers you don't trust.
+pub struct SecurityManager {
+    tool_policies: HashMap<String, ToolPolicy>,
+    derived_policies: HashMap<String, ToolPolicy>,
+    call_history: HashMap<String, Vec<Instant>>,
+}
 
+impl SecurityManager {
+    pub fn new() -> Self {
         SecurityManager {
-            tool_policies: policies,
+            tool_policies: HashMap::new(),
+            derived_policies: HashMap::new(),
             call_history: HashMap::new(),
         }
     }
@@ -50,16 +60,29 @@ impl SecurityManager {
         self.tool_policies.insert(tool_name, policy);
     }
 
-    pub fn check_tool_call(&mut self, tool_name: &str) -> bool {
-        let policy = self
-            .tool_policies
+    /// Derives default policies from the tools' annotations, replacing those
+    /// derived earlier for the same names, e.g. after `tools/list_changed`.
+    pub fn register_tools<'a>(&mut self, tools: impl IntoIterator<Item = &'a Tool>) {
+        for tool in tools {
+            self.derived_policies.insert(
+                tool.name.clone(),
+                ToolPolicy::from_annotations(tool.annotations.as_ref()),
+            );
+        }
+    }
+
+    /// The policy that applies to `tool_name`: explicit, derived, or the
+    /// policy for an unannotated tool.
+    pub fn policy_for(&self, tool_name: &str) -> ToolPolicy {
+        self.tool_policies
             .get(tool_name)
+            .or_else(|| self.derived_policies.get(tool_name))
             .cloned()
-            .unwrap_or(ToolPolicy {
-                requires_approval: true,
-                max_calls_per_minute: 10,
-            });
+            .unwrap_or_else(|| ToolPolicy::from_annotations(None))
+    }
 
+    pub fn check_tool_call(&mut self, tool_name: &str) -> bool {
+        let policy = self.policy_for(tool_name);
         self.check_rate_limit(tool_name, policy.max_calls_per_minute)
     }
 
@@ -70,7 +93,7 @@ impl SecurityManager {
         let history = self
             .call_history
             .entry(tool_name.to_string())
-            .or_insert_with(Vec::new);
+            .or_default();
 
         history.retain(|&call_time| call_time > one_minute_ago);
 
@@ -87,4 +110,4 @@ impl Default for SecurityManager {
     fn default() -> Self {
         Self::new()
     }
-}
\ No newline at end of file
+}
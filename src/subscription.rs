@@ -0,0 +1,83 @@
+use crate::client::{ClientResult, MCPClient};
+use crate::notifications::ServerNotification;
+use crate::types::{ResourceContent, ResourceUpdatedNotification};
+use futures::stream::{self, Stream};
+use tokio::sync::broadcast::{self, error::RecvError};
+
+/// Live `notifications/resources/updated` events for one resource, returned
+/// by `MCPClient::subscribe_resource`.
+///
+/// Dropping a subscription releases it like `unsubscribe`, except that
+/// `resources/unsubscribe` is sent in the background when it was the last
+/// subscription to its resource.
+pub struct ResourceSubscription {
+    client: MCPClient,
+    uri: String,
+    notifications: broadcast::Receiver<ServerNotification>,
+    /// Set by `unsubscribe`, which releases the subscription itself.
+    released: bool,
+}
+
+impl ResourceSubscription {
+    pub(crate) fn new(
+        client: MCPClient,
+        uri: String,
+        notifications: broadcast::Receiver<ServerNotification>,
+    ) -> Self {
+        ResourceSubscription {
+            client,
+            uri,
+            notifications,
+            released: false,
+        }
+    }
+
+    pub fn uri(&self) -> &str {
+        &self.uri
+    }
+
+    /// Waits for the next update to this resource. Returns `None` once the
+    /// connection is gone.
+    pub async fn next(&mut self) -> Option<ResourceUpdatedNotification> {
+        loop {
+            match self.notifications.recv().await {
+                Ok(ServerNotification::ResourceUpdated(update)) if update.uri == self.uri => {
+                    return Some(update);
+                }
+                Ok(_) => continue,
+                // Missed notifications may have included an update, so report one.
+                Err(RecvError::Lagged(_)) => {
+                    return Some(ResourceUpdatedNotification {
+                        uri: self.uri.clone(),
+                    });
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    }
+
+    /// Reads the resource's current contents.
+    pub async fn read(&self) -> ClientResult<ResourceContent> {
+        self.client.read_resource(&self.uri).await
+    }
+
+    pub async fn unsubscribe(mut self) -> ClientResult<()> {
+        self.released = true;
+        self.client.unsubscribe_resource(&self.uri).await
+    }
+
+    pub fn into_stream(self) -> impl Stream<Item = ResourceUpdatedNotification> + Send + 'static {
+        stream::unfold(self, |mut subscription| async move {
+            let update = subscription.next().await?;
+            Some((update, subscription))
+        })
+    }
+}
+
+impl Drop for ResourceSubscription {
+    fn drop(&mut self) {
+        if !self.released {
+            self.client.unsubscribe_resource_detached(&self.uri);
+        }
+    }
+}
@@ -1,10 +1,14 @@
 use async_trait::async_trait;
-use std::io::{BufRead, BufReader, Write};
-use std::process::{Child, Command, Stdio};
-use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::Duration;
 use thiserror::Error;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStdin, ChildStdout, Command};
+use tokio::sync::Mutex;
 
-use crate::types::JsonRpcRequest;
+use crate::types::{JsonRpcNotification, JsonRpcRequest, JsonRpcResponse};
 
 #[derive(Error, Debug)]
 pub enum TransportError {
@@ -20,81 +24,114 @@ pub enum TransportError {
 
 pub type TransportResult<T> = Result<T, TransportError>;
 
+/// A bidirectional JSON-RPC message channel.
+///
+/// `send` and `receive` may be called concurrently: the client keeps a single
+/// background task blocked in `receive` while requests are written with `send`.
 #[async_trait]
 pub trait Transport: Send + Sync {
     async fn send(&self, message: JsonRpcRequest) -> TransportResult<()>;
+    async fn send_notification(&self, notification: JsonRpcNotification) -> TransportResult<()>;
+    /// Sends several requests as one JSON array.
+    async fn send_batch(&self, requests: Vec<JsonRpcRequest>) -> TransportResult<()>;
+    /// Answers a request the server sent to the client.
+    async fn send_response(&self, response: JsonRpcResponse) -> TransportResult<()>;
     async fn receive(&self) -> TransportResult<serde_json::Value>;
     async fn close(&self) -> TransportResult<()>;
 }
 
 pub struct StdioTransport {
     process: Arc<Mutex<Option<Child>>>,
-    reader: Arc<Mutex<Box<dyn std::io::Read + Send>>>,
-    writer: Arc<Mutex<Box<dyn std::io::Write + Send>>>,
+    reader: Arc<Mutex<BufReader<ChildStdout>>>,
+    writer: Arc<Mutex<ChildStdin>>,
 }
 
 impl StdioTransport {
+    /// Spawns the server process. Must be called from within a Tokio runtime.
     pub fn new(command: &str, args: &[&str]) -> TransportResult<Self> {
         let mut child = Command::new(command)
             .args(args)
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
             .stderr(Stdio::inherit())
+            .kill_on_drop(true)
             .spawn()?;
 
-        let stdout = child.stdout.take().ok_or_else(|| {
-            std::io::Error::new(std::io::ErrorKind::Other, "Failed to capture stdout")
-        })?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| std::io::Error::other("Failed to capture stdout"))?;
 
-        let stdin = child.stdin.take().ok_or_else(|| {
-            std::io::Error::new(std::io::ErrorKind::Other, "Failed to capture stdin")
-        })?;
+        let stdin = child
+            .stdin
+            .take()
+            .ok_or_else(|| std::io::Error::other("Failed to capture stdin"))?;
 
         Ok(StdioTransport {
             process: Arc::new(Mutex::new(Some(child))),
-            reader: Arc::new(Mutex::new(Box::new(stdout))),
-            writer: Arc::new(Mutex::new(Box::new(stdin))),
+            reader: Arc::new(Mutex::new(BufReader::new(stdout))),
+            writer: Arc::new(Mutex::new(stdin)),
         })
     }
+
+    async fn write_message<T: Serialize>(&self, message: &T) -> TransportResult<()> {
+        let mut json = serde_json::to_string(message)?;
+        json.push('\n');
+        let mut writer = self.writer.lock().await;
+        writer.write_all(json.as_bytes()).await?;
+        writer.flush().await?;
+        Ok(())
+    }
 }
 
 #[async_trait]
 impl Transport for StdioTransport {
     async fn send(&self, message: JsonRpcRequest) -> TransportResult<()> {
-        let json = serde_json::to_string(&message)?;
-        let mut writer = self.writer.lock().map_err(|_| {
-            TransportError::ConnectionError("Failed to acquire writer lock".to_string())
-        })?;
-        writer.write_all(json.as_bytes())?;
-        writer.write_all(b"\n")?;
-        writer.flush()?;
-        Ok(())
+        self.write_message(&message).await
+    }
+
+    async fn send_notification(&self, notification: JsonRpcNotification) -> TransportResult<()> {
+        self.write_message(&notification).await
+    }
+
+    async fn send_batch(&self, requests: Vec<JsonRpcRequest>) -> TransportResult<()> {
+        self.write_message(&requests).await
+    }
+
+    async fn send_response(&self, response: JsonRpcResponse) -> TransportResult<()> {
+        self.write_message(&response).await
     }
 
     async fn receive(&self) -> TransportResult<serde_json::Value> {
-        let mut reader = self.reader.lock().map_err(|_| {
-            TransportError::ConnectionError("Failed to acquire reader lock".to_string())
-        })?;
-        let mut buf_reader = BufReader::new(&mut *reader);
+        let mut reader = self.reader.lock().await;
         let mut line = String::new();
-        
-        if buf_reader.read_line(&mut line)? == 0 {
-            return Err(TransportError::Closed);
+
+        loop {
+            line.clear();
+            if reader.read_line(&mut line).await? == 0 {
+                return Err(TransportError::Closed);
+            }
+            if !line.trim().is_empty() {
+                break;
+            }
         }
 
         Ok(serde_json::from_str(&line)?)
     }
 
     async fn close(&self) -> TransportResult<()> {
-        if let Ok(mut process_guard) = self.process.lock() {
-            if let Some(mut process) = process_guard.take() {
-                process.kill()?;
-            }
+        if let Some(mut process) = self.process.lock().await.take() {
+            process.kill().await?;
         }
         Ok(())
     }
 }
 
+/// First wait before polling an empty events endpoint again, doubling up
+/// to `MAX_POLL_INTERVAL`.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+const MAX_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
 pub struct HttpSSETransport {
     base_url: String,
     client: reqwest::Client,
@@ -109,39 +146,73 @@ impl HttpSSETransport {
             session_id: uuid::Uuid::new_v4().to_string(),
         }
     }
-}
 
-#[async_trait]
-impl Transport for HttpSSETransport {
-    async fn send(&self, message: JsonRpcRequest) -> TransportResult<()> {
+    async fn post_message<T: Serialize + Sync>(&self, message: &T) -> TransportResult<()> {
         let url = format!("{}/rpc", self.base_url);
         
         self.client
             .post(&url)
             .header("Content-Type", "application/json")
-            .json(&message)
+            .json(message)
             .send()
             .await
             .map_err(|e| TransportError::ConnectionError(e.to_string()))?;
 
         Ok(())
     }
+}
+
+#[async_trait]
+impl Transport for HttpSSETransport {
+    async fn send(&self, message: JsonRpcRequest) -> TransportResult<()> {
+        self.post_message(&message).await
+    }
+
+    async fn send_notification(&self, notification: JsonRpcNotification) -> TransportResult<()> {
+        self.post_message(&notification).await
+    }
+
+    async fn send_batch(&self, requests: Vec<JsonRpcRequest>) -> TransportResult<()> {
+        self.post_message(&requests).await
+    }
+
+    async fn send_response(&self, response: JsonRpcResponse) -> TransportResult<()> {
+        self.post_message(&response).await
+    }
 
+    /// Polls the events endpoint until a message arrives, backing off while
+    /// it has none. An HTTP error status ends the session.
     async fn receive(&self) -> TransportResult<serde_json::Value> {
         let url = format!("{}/events/{}", self.base_url, self.session_id);
-        
-        let response = self.client
-            .get(&url)
-            .send()
-            .await
-            .map_err(|e| TransportError::ConnectionError(e.to_string()))?;
+        let mut delay = POLL_INTERVAL;
+
+        loop {
+            let response = self.client
+                .get(&url)
+                .send()
+                .await
+                .map_err(|e| TransportError::ConnectionError(e.to_string()))?;
+
+            let status = response.status();
+            if !status.is_success() {
+                return Err(TransportError::ConnectionError(format!(
+                    "{} returned {}",
+                    url, status
+                )));
+            }
 
-        let text = response
-            .text()
-            .await
-            .map_err(|e| TransportError::ConnectionError(e.to_string()))?;
+            let text = response
+                .text()
+                .await
+                .map_err(|e| TransportError::ConnectionError(e.to_string()))?;
 
-        Ok(serde_json::from_str(&text)?)
+            if !text.trim().is_empty() {
+                return Ok(serde_json::from_str(&text)?);
+            }
+
+            tokio::time::sleep(delay).await;
+            delay = (delay * 2).min(MAX_POLL_INTERVAL);
+        }
     }
 
     async fn close(&self) -> TransportResult<()> {
@@ -12,42 +12,266 @@ pub struct ServerInfo {
     pub version: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Capabilities advertised by the server in its `initialize` result.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Capabilities {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub tools: Option<ToolCapability>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub resources: Option<ResourceCapability>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub prompts: Option<PromptCapability>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logging: Option<LoggingCapability>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completions: Option<CompletionsCapability>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub experimental: Option<serde_json::Value>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ToolCapability {}
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ToolCapability {
+    #[serde(rename = "listChanged", skip_serializing_if = "Option::is_none")]
+    pub list_changed: Option<bool>,
+}
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ResourceCapability {}
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ResourceCapability {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subscribe: Option<bool>,
+    #[serde(rename = "listChanged", skip_serializing_if = "Option::is_none")]
+    pub list_changed: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PromptCapability {
+    #[serde(rename = "listChanged", skip_serializing_if = "Option::is_none")]
+    pub list_changed: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LoggingCapability {}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CompletionsCapability {}
+
+/// Capabilities the client advertises in its `initialize` request.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ClientCapabilities {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub roots: Option<RootsCapability>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sampling: Option<SamplingCapability>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub elicitation: Option<ElicitationCapability>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub experimental: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RootsCapability {
+    #[serde(rename = "listChanged", skip_serializing_if = "Option::is_none")]
+    pub list_changed: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SamplingCapability {}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ElicitationCapability {}
+
+/// A directory or file the client lets servers work in, as a `file://` URI.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Root {
+    pub uri: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+impl Root {
+    pub fn new(uri: &str, name: Option<&str>) -> Self {
+        Root {
+            uri: uri.to_string(),
+            name: name.map(str::to_string),
+        }
+    }
+
+    /// A root for a local path, made absolute against the current directory.
+    pub fn from_path(path: impl AsRef<std::path::Path>, name: Option<&str>) -> std::io::Result<Self> {
+        let path = std::path::absolute(path)?;
+        let uri = url::Url::from_file_path(&path).map_err(|()| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("Cannot express {} as a file URI", path.display()),
+            )
+        })?;
+        Ok(Root {
+            uri: uri.to_string(),
+            name: name.map(str::to_string),
+        })
+    }
+}
+
+/// MCP specification revisions this client can speak, oldest first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum ProtocolVersion {
+    #[serde(rename = "2024-11-05")]
+    V2024_11_05,
+    #[serde(rename = "2025-03-26")]
+    V2025_03_26,
+    #[serde(rename = "2025-06-18")]
+    V2025_06_18,
+}
+
+impl ProtocolVersion {
+    pub const LATEST: ProtocolVersion = ProtocolVersion::V2025_06_18;
+
+    pub const SUPPORTED: &'static [ProtocolVersion] = &[
+        ProtocolVersion::V2024_11_05,
+        ProtocolVersion::V2025_03_26,
+        ProtocolVersion::V2025_06_18,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ProtocolVersion::V2024_11_05 => "2024-11-05",
+            ProtocolVersion::V2025_03_26 => "2025-03-26",
+            ProtocolVersion::V2025_06_18 => "2025-06-18",
+        }
+    }
+
+    /// Whether tools may declare `outputSchema` and return `structuredContent`.
+    pub fn supports_structured_output(self) -> bool {
+        self >= ProtocolVersion::V2025_06_18
+    }
+
+    /// Whether servers may send `elicitation/create` requests.
+    pub fn supports_elicitation(self) -> bool {
+        self >= ProtocolVersion::V2025_06_18
+    }
+
+    /// Whether JSON-RPC batches may be sent; 2025-06-18 removed them again.
+    pub fn supports_batching(self) -> bool {
+        self == ProtocolVersion::V2025_03_26
+    }
+
+    /// Whether tool results may contain `resource_link` content.
+    pub fn supports_resource_links(self) -> bool {
+        self >= ProtocolVersion::V2025_06_18
+    }
+}
+
+impl std::fmt::Display for ProtocolVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for ProtocolVersion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ProtocolVersion::SUPPORTED
+            .iter()
+            .copied()
+            .find(|version| version.as_str() == s)
+            .ok_or_else(|| s.to_string())
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct PromptCapability {}
+pub struct InitializeResult {
+    #[serde(rename = "protocolVersion")]
+    pub protocol_version: String,
+    pub capabilities: Capabilities,
+    #[serde(rename = "serverInfo")]
+    pub server_info: ServerInfo,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instructions: Option<String>,
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Tool {
     pub name: String,
+    /// Shown in place of `name`; preferred over `annotations.title`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
     pub description: Option<String>,
     #[serde(rename = "inputSchema")]
     pub input_schema: serde_json::Value,
+    /// JSON schema of the tool's `structuredContent`, from 2025-06-18.
+    #[serde(rename = "outputSchema", default, skip_serializing_if = "Option::is_none")]
+    pub output_schema: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub annotations: Option<ToolAnnotations>,
+}
+
+/// The server's description of a tool's behavior. These are hints from the
+/// server, not guarantees; missing hints take the spec's cautious defaults.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolAnnotations {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub read_only_hint: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub destructive_hint: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub idempotent_hint: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub open_world_hint: Option<bool>,
+}
+
+impl ToolAnnotations {
+    /// The tool doesn't modify its environment. Defaults to false.
+    pub fn is_read_only(&self) -> bool {
+        self.read_only_hint.unwrap_or(false)
+    }
+
+    /// The tool may destroy or overwrite data, rather than only add to it.
+    /// Defaults to true; always false for read-only tools.
+    pub fn is_destructive(&self) -> bool {
+        !self.is_read_only() && self.destructive_hint.unwrap_or(true)
+    }
+
+    /// Repeating a call with the same arguments has no further effect.
+    /// Defaults to false.
+    pub fn is_idempotent(&self) -> bool {
+        self.idempotent_hint.unwrap_or(false)
+    }
+
+    /// The tool reaches outside the server, e.g. the web. Defaults to true.
+    pub fn is_open_world(&self) -> bool {
+        self.open_world_hint.unwrap_or(true)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Resource {
     pub uri: String,
     pub name: String,
+    /// Display name, from 2025-06-18; `name` is the programmatic one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
     pub description: Option<String>,
+    #[serde(rename = "mimeType", default, skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+    /// Size in bytes, before any base64 encoding.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub annotations: Option<Annotations>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResourceTemplate {
+    #[serde(rename = "uriTemplate")]
     pub uri_template: String,
     pub name: String,
     pub description: Option<String>,
+    #[serde(rename = "mimeType", skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -64,6 +288,41 @@ pub struct PromptArgument {
     pub required: Option<bool>,
 }
 
+/// One page of a paginated list result.
+#[derive(Debug, Clone)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// Cursor for the next page; `None` on the last page.
+    pub next_cursor: Option<String>,
+}
+
+/// What an argument being completed belongs to.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum CompletionReference {
+    #[serde(rename = "ref/prompt")]
+    Prompt { name: String },
+    /// A resource template, identified by its URI template.
+    #[serde(rename = "ref/resource")]
+    Resource { uri: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompletionArgument {
+    pub name: String,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Completion {
+    pub values: Vec<String>,
+    /// Total number of matches, which may exceed `values.len()`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total: Option<u64>,
+    #[serde(rename = "hasMore", skip_serializing_if = "Option::is_none")]
+    pub has_more: Option<bool>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TextContent {
     pub text: String,
@@ -71,46 +330,325 @@ pub struct TextContent {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToolResult {
-    pub content: Vec<ToolResultContent>,
+    pub content: Vec<Content>,
     #[serde(rename = "isError")]
     pub is_error: Option<bool>,
+    /// Machine-readable result matching the tool's `outputSchema`.
+    #[serde(rename = "structuredContent", default, skip_serializing_if = "Option::is_none")]
+    pub structured_content: Option<serde_json::Value>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(untagged)]
-pub enum ToolResultContent {
-    Text { text: String },
-    Blob { blob: String },
+impl ToolResult {
+    pub fn is_error(&self) -> bool {
+        self.is_error.unwrap_or(false)
+    }
+
+    /// The text parts of the result joined by newlines; other content is skipped.
+    pub fn text(&self) -> String {
+        self.content
+            .iter()
+            .filter_map(|content| match content {
+                Content::Text { text, .. } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResourceContent {
-    pub contents: Vec<ContentItem>,
+    pub contents: Vec<ResourceContents>,
+}
+
+impl ResourceContent {
+    /// Writes every part into `dir` (see `ResourceContents::save_to`),
+    /// numbering parts whose file names would clash.
+    pub async fn save_to(&self, dir: impl AsRef<std::path::Path>) -> std::io::Result<Vec<std::path::PathBuf>> {
+        let dir = dir.as_ref();
+        let mut saved = Vec::with_capacity(self.contents.len());
+        for contents in &self.contents {
+            let mut name = contents.file_name();
+            if saved.iter().any(|path: &std::path::PathBuf| path.ends_with(&name)) {
+                name = numbered(&name, saved.len());
+            }
+            saved.push(contents.write(dir.join(name)).await?);
+        }
+        Ok(saved)
+    }
 }
 
+fn numbered(file_name: &str, n: usize) -> String {
+    match file_name.rsplit_once('.') {
+        Some((stem, extension)) => format!("{}-{}.{}", stem, n, extension),
+        None => format!("{}-{}", file_name, n),
+    }
+}
+
+/// What a resource holds: `text`, or base64 `blob` for binary data.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(untagged)]
-pub enum ContentItem {
-    Text { text: String },
-    Blob { blob: String },
+pub enum ResourceContents {
+    Text {
+        uri: String,
+        #[serde(rename = "mimeType", default, skip_serializing_if = "Option::is_none")]
+        mime_type: Option<String>,
+        text: String,
+    },
+    Blob {
+        uri: String,
+        #[serde(rename = "mimeType", default, skip_serializing_if = "Option::is_none")]
+        mime_type: Option<String>,
+        blob: String,
+    },
 }
 
+impl ResourceContents {
+    pub fn uri(&self) -> &str {
+        match self {
+            ResourceContents::Text { uri, .. } | ResourceContents::Blob { uri, .. } => uri,
+        }
+    }
+
+    pub fn mime_type(&self) -> Option<&str> {
+        match self {
+            ResourceContents::Text { mime_type, .. } | ResourceContents::Blob { mime_type, .. } => {
+                mime_type.as_deref()
+            }
+        }
+    }
+
+    pub fn text(&self) -> Option<&str> {
+        match self {
+            ResourceContents::Text { text, .. } => Some(text),
+            ResourceContents::Blob { .. } => None,
+        }
+    }
+
+    /// The decoded `blob`, or `None` for text contents.
+    pub fn decode_blob(&self) -> Option<Result<Vec<u8>, base64::DecodeError>> {
+        match self {
+            ResourceContents::Text { .. } => None,
+            ResourceContents::Blob { blob, .. } => Some(decode_base64(blob)),
+        }
+    }
+
+    /// The raw contents: UTF-8 text, or the decoded blob.
+    pub fn bytes(&self) -> Result<Vec<u8>, base64::DecodeError> {
+        match self {
+            ResourceContents::Text { text, .. } => Ok(text.clone().into_bytes()),
+            ResourceContents::Blob { blob, .. } => decode_base64(blob),
+        }
+    }
+
+    /// A file name for the contents: the URI's last path segment (or host)
+    /// with unsafe characters replaced, plus an extension from the MIME type
+    /// if it has none.
+    pub fn file_name(&self) -> String {
+        let segment = url::Url::parse(self.uri())
+            .ok()
+            .and_then(|uri| {
+                uri.path_segments()
+                    .and_then(|mut segments| segments.rfind(|segment| !segment.is_empty()))
+                    .or(uri.host_str())
+                    .map(str::to_string)
+            })
+            .unwrap_or_default();
+        let mut name: String = segment
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || "._-".contains(c) { c } else { '_' })
+            .collect();
+        if name.trim_matches('.').is_empty() {
+            name = "resource".to_string();
+        }
+
+        if std::path::Path::new(&name).extension().is_none() {
+            let extension = self.mime_type().and_then(extension_for_mime).or(match self {
+                ResourceContents::Text { .. } => Some("txt"),
+                ResourceContents::Blob { .. } => None,
+            });
+            if let Some(extension) = extension {
+                name = format!("{}.{}", name, extension);
+            }
+        }
+        name
+    }
+
+    /// Writes the contents to `dir` under `file_name()` and returns the path.
+    pub async fn save_to(&self, dir: impl AsRef<std::path::Path>) -> std::io::Result<std::path::PathBuf> {
+        self.write(dir.as_ref().join(self.file_name())).await
+    }
+
+    async fn write(&self, path: std::path::PathBuf) -> std::io::Result<std::path::PathBuf> {
+        let bytes = self
+            .bytes()
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        tokio::fs::write(&path, bytes).await?;
+        Ok(path)
+    }
+}
+
+/// The usual file extension for a MIME type, ignoring parameters such as
+/// `charset`.
+pub fn extension_for_mime(mime_type: &str) -> Option<&'static str> {
+    let essence = mime_type.split(';').next().unwrap_or_default().trim().to_ascii_lowercase();
+    let extension = match essence.as_str() {
+        "text/plain" => "txt",
+        "text/markdown" => "md",
+        "text/html" => "html",
+        "text/css" => "css",
+        "text/csv" => "csv",
+        "text/javascript" | "application/javascript" => "js",
+        "text/xml" | "application/xml" => "xml",
+        "text/x-python" => "py",
+        "text/x-rust" => "rs",
+        "application/json" => "json",
+        "application/yaml" | "text/yaml" => "yaml",
+        "application/toml" => "toml",
+        "application/pdf" => "pdf",
+        "application/zip" => "zip",
+        "application/gzip" => "gz",
+        "application/octet-stream" => "bin",
+        "image/png" => "png",
+        "image/jpeg" => "jpg",
+        "image/gif" => "gif",
+        "image/webp" => "webp",
+        "image/svg+xml" => "svg",
+        "audio/mpeg" => "mp3",
+        "audio/wav" | "audio/x-wav" => "wav",
+        "audio/ogg" => "ogg",
+        "video/mp4" => "mp4",
+        _ => return None,
+    };
+    Some(extension)
+}
+
+/// Hints about how content is meant to be used.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Annotations {
+    /// Who the content is for: `"user"`, `"assistant"` or both.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub audience: Option<Vec<String>>,
+    /// From 0 (optional) to 1 (required).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub priority: Option<f64>,
+    /// ISO 8601 timestamp.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_modified: Option<String>,
+}
+
+/// A block of content in tool results, prompt messages and sampling
+/// messages, tagged by `type`.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct PromptsResult {
-    pub messages: Vec<PromptMessage>,
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Content {
+    Text {
+        text: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        annotations: Option<Annotations>,
+    },
+    /// Base64-encoded image data.
+    Image {
+        data: String,
+        #[serde(rename = "mimeType")]
+        mime_type: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        annotations: Option<Annotations>,
+    },
+    /// Base64-encoded audio data.
+    Audio {
+        data: String,
+        #[serde(rename = "mimeType")]
+        mime_type: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        annotations: Option<Annotations>,
+    },
+    /// A resource's contents, inline.
+    Resource {
+        resource: ResourceContents,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        annotations: Option<Annotations>,
+    },
+    /// A resource the client can read with `resources/read`, from 2025-06-18.
+    ResourceLink {
+        uri: String,
+        name: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        title: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        description: Option<String>,
+        #[serde(rename = "mimeType", default, skip_serializing_if = "Option::is_none")]
+        mime_type: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        size: Option<u64>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        annotations: Option<Annotations>,
+    },
+}
+
+impl Content {
+    pub fn text(text: impl Into<String>) -> Self {
+        Content::Text {
+            text: text.into(),
+            annotations: None,
+        }
+    }
+
+    /// The text of a text block or of an embedded text resource.
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            Content::Text { text, .. } => Some(text),
+            Content::Resource { resource, .. } => resource.text(),
+            _ => None,
+        }
+    }
+
+    pub fn mime_type(&self) -> Option<&str> {
+        match self {
+            Content::Text { .. } => None,
+            Content::Image { mime_type, .. } | Content::Audio { mime_type, .. } => Some(mime_type),
+            Content::Resource { resource, .. } => resource.mime_type(),
+            Content::ResourceLink { mime_type, .. } => mime_type.as_deref(),
+        }
+    }
+
+    pub fn annotations(&self) -> Option<&Annotations> {
+        match self {
+            Content::Text { annotations, .. }
+            | Content::Image { annotations, .. }
+            | Content::Audio { annotations, .. }
+            | Content::Resource { annotations, .. }
+            | Content::ResourceLink { annotations, .. } => annotations.as_ref(),
+        }
+    }
+
+    /// The decoded bytes of image, audio and embedded blob content; `None`
+    /// for anything else.
+    pub fn decode_data(&self) -> Option<Result<Vec<u8>, base64::DecodeError>> {
+        match self {
+            Content::Image { data, .. } | Content::Audio { data, .. } => Some(decode_base64(data)),
+            Content::Resource { resource, .. } => resource.decode_blob(),
+            _ => None,
+        }
+    }
+}
+
+/// Decodes standard base64, as used for all binary data in MCP.
+pub fn decode_base64(data: &str) -> Result<Vec<u8>, base64::DecodeError> {
+    use base64::Engine;
+    base64::engine::general_purpose::STANDARD.decode(data)
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(untagged)]
-pub enum MessageContent {
-    Text { text: String },
-    Blob { blob: String },
+pub struct PromptsResult {
+    pub messages: Vec<PromptMessage>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PromptMessage {
     pub role: String,
-    pub content: Vec<MessageContent>,
+    pub content: Content,
 }
 
 // JSON-RPC structures
@@ -141,10 +679,415 @@ pub struct JsonRpcError {
     pub data: Option<serde_json::Value>,
 }
 
+impl JsonRpcError {
+    pub const PARSE_ERROR: i32 = -32700;
+    pub const INVALID_REQUEST: i32 = -32600;
+    pub const METHOD_NOT_FOUND: i32 = -32601;
+    pub const INVALID_PARAMS: i32 = -32602;
+    pub const INTERNAL_ERROR: i32 = -32603;
+    /// MCP's code for reading a resource that doesn't exist.
+    pub const RESOURCE_NOT_FOUND: i32 = -32002;
+
+    pub fn new(code: i32, message: impl Into<String>) -> Self {
+        JsonRpcError {
+            code,
+            message: message.into(),
+            data: None,
+        }
+    }
+
+    pub fn is_parse_error(&self) -> bool {
+        self.code == Self::PARSE_ERROR
+    }
+
+    pub fn is_invalid_request(&self) -> bool {
+        self.code == Self::INVALID_REQUEST
+    }
+
+    pub fn is_method_not_found(&self) -> bool {
+        self.code == Self::METHOD_NOT_FOUND
+    }
+
+    pub fn is_invalid_params(&self) -> bool {
+        self.code == Self::INVALID_PARAMS
+    }
+
+    pub fn is_internal_error(&self) -> bool {
+        self.code == Self::INTERNAL_ERROR
+    }
+
+    pub fn is_resource_not_found(&self) -> bool {
+        self.code == Self::RESOURCE_NOT_FOUND
+    }
+
+    /// Codes in -32099..=-32000, reserved for implementation-defined server errors.
+    pub fn is_server_error(&self) -> bool {
+        (-32099..=-32000).contains(&self.code)
+    }
+}
+
+impl std::fmt::Display for JsonRpcError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} (code {})", self.message, self.code)
+    }
+}
+
+impl std::error::Error for JsonRpcError {}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct JsonRpcNotification {
     pub jsonrpc: String,
     pub method: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub params: Option<serde_json::Value>,
-}
\ No newline at end of file
+}
+// Notification payloads
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProgressNotification {
+    #[serde(rename = "progressToken")]
+    pub progress_token: serde_json::Value,
+    pub progress: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+/// Syslog-style severities used by MCP logging, least severe first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LoggingLevel {
+    Debug,
+    Info,
+    Notice,
+    Warning,
+    Error,
+    Critical,
+    Alert,
+    Emergency,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoggingMessageNotification {
+    pub level: LoggingLevel,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logger: Option<String>,
+    pub data: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResourceUpdatedNotification {
+    pub uri: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CancelledNotification {
+    #[serde(rename = "requestId")]
+    pub request_id: serde_json::Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+// Sampling (server-to-client `sampling/createMessage`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateMessageRequest {
+    pub messages: Vec<SamplingMessage>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model_preferences: Option<ModelPreferences>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system_prompt: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_context: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    pub max_tokens: u32,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub stop_sequences: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SamplingMessage {
+    pub role: String,
+    /// Text, image or audio.
+    pub content: Content,
+}
+
+/// The server's hints for choosing a model; priorities range from 0 to 1.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelPreferences {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hints: Vec<ModelHint>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cost_priority: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub speed_priority: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub intelligence_priority: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelHint {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateMessageResult {
+    pub role: String,
+    /// Text, image or audio.
+    pub content: Content,
+    pub model: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop_reason: Option<String>,
+}
+
+// Elicitation (server-to-client `elicitation/create`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ElicitRequest {
+    pub message: String,
+    pub requested_schema: ElicitationSchema,
+}
+
+/// The flat object schema servers may request: primitive properties only.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ElicitationSchema {
+    #[serde(rename = "type", default = "object_type")]
+    pub schema_type: String,
+    pub properties: std::collections::BTreeMap<String, ElicitationProperty>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub required: Vec<String>,
+}
+
+fn object_type() -> String {
+    "object".to_string()
+}
+
+/// A string, number, integer, boolean or enum property.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ElicitationProperty {
+    #[serde(rename = "type")]
+    pub property_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(rename = "enum", skip_serializing_if = "Option::is_none")]
+    pub enum_values: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enum_names: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_length: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_length: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub minimum: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub maximum: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ElicitAction {
+    Accept,
+    Decline,
+    Cancel,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ElicitResult {
+    pub action: ElicitAction,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<serde_json::Map<String, serde_json::Value>>,
+}
+
+impl ElicitResult {
+    pub fn accept(content: serde_json::Map<String, serde_json::Value>) -> Self {
+        ElicitResult {
+            action: ElicitAction::Accept,
+            content: Some(content),
+        }
+    }
+
+    pub fn decline() -> Self {
+        ElicitResult {
+            action: ElicitAction::Decline,
+            content: None,
+        }
+    }
+
+    pub fn cancel() -> Self {
+        ElicitResult {
+            action: ElicitAction::Cancel,
+            content: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// Deserializes `fixture` and checks it serializes back unchanged.
+    fn round_trip<T: serde::de::DeserializeOwned + Serialize>(fixture: serde_json::Value) -> T {
+        let value: T = serde_json::from_value(fixture.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&value).unwrap(), fixture);
+        value
+    }
+
+    #[test]
+    fn text_content() {
+        let content: Content = round_trip(json!({
+            "type": "text",
+            "text": "Hello",
+            "annotations": { "audience": ["user"], "priority": 0.5 }
+        }));
+        assert_eq!(content.as_text(), Some("Hello"));
+        assert_eq!(content.annotations().and_then(|a| a.priority), Some(0.5));
+    }
+
+    #[test]
+    fn image_content() {
+        let content: Content = round_trip(json!({
+            "type": "image",
+            "data": "aGVsbG8=",
+            "mimeType": "image/png"
+        }));
+        assert!(matches!(content, Content::Image { .. }));
+        assert_eq!(content.mime_type(), Some("image/png"));
+        assert_eq!(content.decode_data().unwrap().unwrap(), b"hello");
+    }
+
+    #[test]
+    fn audio_content() {
+        let content: Content = round_trip(json!({
+            "type": "audio",
+            "data": "aGk=",
+            "mimeType": "audio/wav"
+        }));
+        assert!(matches!(content, Content::Audio { .. }));
+        assert_eq!(content.decode_data().unwrap().unwrap(), b"hi");
+    }
+
+    #[test]
+    fn embedded_text_resource() {
+        let content: Content = round_trip(json!({
+            "type": "resource",
+            "resource": { "uri": "file:///notes.md", "mimeType": "text/markdown", "text": "# Notes" }
+        }));
+        let Content::Resource { resource, .. } = &content else {
+            panic!("expected a resource, got {:?}", content);
+        };
+        assert!(matches!(resource, ResourceContents::Text { .. }));
+        assert_eq!(resource.uri(), "file:///notes.md");
+        assert_eq!(content.as_text(), Some("# Notes"));
+    }
+
+    #[test]
+    fn embedded_blob_resource() {
+        let content: Content = round_trip(json!({
+            "type": "resource",
+            "resource": { "uri": "file:///logo.png", "mimeType": "image/png", "blob": "aGVsbG8=" }
+        }));
+        let Content::Resource { resource, .. } = &content else {
+            panic!("expected a resource, got {:?}", content);
+        };
+        assert!(matches!(resource, ResourceContents::Blob { .. }));
+        assert_eq!(resource.text(), None);
+        assert_eq!(content.decode_data().unwrap().unwrap(), b"hello");
+    }
+
+    #[test]
+    fn resource_link_content() {
+        let content: Content = round_trip(json!({
+            "type": "resource_link",
+            "uri": "file:///src/main.rs",
+            "name": "main.rs",
+            "description": "Entry point",
+            "mimeType": "text/x-rust",
+            "size": 120
+        }));
+        assert!(matches!(content, Content::ResourceLink { size: Some(120), .. }));
+        assert_eq!(content.mime_type(), Some("text/x-rust"));
+    }
+
+    #[test]
+    fn unknown_content_type_is_an_error() {
+        assert!(serde_json::from_value::<Content>(json!({ "type": "video", "data": "" })).is_err());
+    }
+
+    #[test]
+    fn resource_contents_keep_uri_and_mime_type() {
+        let read: ResourceContent = round_trip(json!({
+            "contents": [
+                { "uri": "file:///a.txt", "mimeType": "text/plain", "text": "a" },
+                { "uri": "file:///b.bin", "blob": "aGk=" }
+            ]
+        }));
+        assert_eq!(read.contents[0].mime_type(), Some("text/plain"));
+        assert_eq!(read.contents[1].uri(), "file:///b.bin");
+        assert_eq!(read.contents[1].bytes().unwrap(), b"hi");
+    }
+
+    fn text_contents(uri: &str, mime_type: Option<&str>) -> ResourceContents {
+        ResourceContents::Text {
+            uri: uri.to_string(),
+            mime_type: mime_type.map(str::to_string),
+            text: "hello".to_string(),
+        }
+    }
+
+    #[test]
+    fn file_name_never_climbs_out_of_the_directory() {
+        assert_eq!(text_contents("file:///etc/..", None).file_name(), "resource.txt");
+        assert_eq!(text_contents("notes:..", None).file_name(), "resource.txt");
+        assert_eq!(text_contents("file:///a/..%2F..%2Fetc", None).file_name(), ".._2F.._2Fetc");
+    }
+
+    #[test]
+    fn file_name_falls_back_to_the_host() {
+        assert_eq!(text_contents("https://example.com", None).file_name(), "example.com");
+        assert_eq!(text_contents("https://example.com/", Some("text/html")).file_name(), "example.com");
+    }
+
+    #[test]
+    fn file_name_gets_an_extension_from_the_mime_type() {
+        assert_eq!(text_contents("config://app/settings", Some("application/json")).file_name(), "settings.json");
+        assert_eq!(text_contents("file:///README.md", Some("text/plain")).file_name(), "README.md");
+        let blob = ResourceContents::Blob {
+            uri: "db://images/logo".to_string(),
+            mime_type: Some("image/png".to_string()),
+            blob: "aGk=".to_string(),
+        };
+        assert_eq!(blob.file_name(), "logo.png");
+    }
+
+    #[tokio::test]
+    async fn save_to_numbers_clashing_file_names() {
+        let dir = tempfile::tempdir().unwrap();
+        let read = ResourceContent {
+            contents: vec![
+                text_contents("file:///a/notes.txt", None),
+                text_contents("file:///b/notes.txt", None),
+            ],
+        };
+
+        let saved = read.save_to(dir.path()).await.unwrap();
+
+        assert_eq!(saved, vec![dir.path().join("notes.txt"), dir.path().join("notes-1.txt")]);
+        for path in &saved {
+            assert_eq!(std::fs::read_to_string(path).unwrap(), "hello");
+        }
+    }
+}
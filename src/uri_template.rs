@@ -0,0 +1,267 @@
+//! RFC 6570 URI template expansion, levels 1 to 3.
+//!
+//! Values are plain strings; level 4 modifiers (`:n` prefixes and `*`
+//! explode) are rejected.
+
+use std::collections::HashMap;
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum UriTemplateError {
+    #[error("Unclosed expression in URI template: {0}")]
+    UnclosedExpression(String),
+    #[error("Unsupported URI template expression: {{{0}}}")]
+    UnsupportedExpression(String),
+}
+
+/// How an expression operator renders its variables (RFC 6570, appendix A).
+struct Operator {
+    first: &'static str,
+    separator: &'static str,
+    named: bool,
+    if_empty: &'static str,
+    allow_reserved: bool,
+}
+
+impl Operator {
+    fn parse(expression: &str) -> (Operator, &str) {
+        let op = |first, separator, named, if_empty, allow_reserved| Operator {
+            first,
+            separator,
+            named,
+            if_empty,
+            allow_reserved,
+        };
+
+        match expression.chars().next() {
+            Some('+') => (op("", ",", false, "", true), &expression[1..]),
+            Some('#') => (op("#", ",", false, "", true), &expression[1..]),
+            Some('.') => (op(".", ".", false, "", false), &expression[1..]),
+            Some('/') => (op("/", "/", false, "", false), &expression[1..]),
+            Some(';') => (op(";", ";", true, "", false), &expression[1..]),
+            Some('?') => (op("?", "&", true, "=", false), &expression[1..]),
+            Some('&') => (op("&", "&", true, "=", false), &expression[1..]),
+            _ => (op("", ",", false, "", false), expression),
+        }
+    }
+}
+
+/// Expands `template`, leaving out variables missing from `variables`.
+pub fn expand(template: &str, variables: &HashMap<String, String>) -> Result<String, UriTemplateError> {
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        output.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let end = after
+            .find('}')
+            .ok_or_else(|| UriTemplateError::UnclosedExpression(template.to_string()))?;
+        expand_expression(&after[..end], variables, &mut output)?;
+        rest = &after[end + 1..];
+    }
+    output.push_str(rest);
+
+    Ok(output)
+}
+
+/// Names of the variables referenced by `template`, in order of appearance.
+pub fn variable_names(template: &str) -> Result<Vec<String>, UriTemplateError> {
+    let mut names = Vec::new();
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        let after = &rest[start + 1..];
+        let end = after
+            .find('}')
+            .ok_or_else(|| UriTemplateError::UnclosedExpression(template.to_string()))?;
+        let (_, list) = Operator::parse(&after[..end]);
+        for name in list.split(',') {
+            let name = validate_name(&after[..end], name)?;
+            if !names.iter().any(|existing| existing == name) {
+                names.push(name.to_string());
+            }
+        }
+        rest = &after[end + 1..];
+    }
+
+    Ok(names)
+}
+
+fn expand_expression(
+    expression: &str,
+    variables: &HashMap<String, String>,
+    output: &mut String,
+) -> Result<(), UriTemplateError> {
+    let (operator, list) = Operator::parse(expression);
+    let mut first = true;
+
+    for name in list.split(',') {
+        let name = validate_name(expression, name)?;
+        let Some(value) = variables.get(name) else {
+            continue;
+        };
+
+        output.push_str(if first { operator.first } else { operator.separator });
+        first = false;
+
+        if operator.named {
+            output.push_str(name);
+            if value.is_empty() {
+                output.push_str(operator.if_empty);
+                continue;
+            }
+            output.push('=');
+        }
+        encode(value, operator.allow_reserved, output);
+    }
+
+    Ok(())
+}
+
+fn validate_name<'a>(expression: &str, name: &'a str) -> Result<&'a str, UriTemplateError> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.' || c == '%');
+    if valid {
+        Ok(name)
+    } else {
+        Err(UriTemplateError::UnsupportedExpression(expression.to_string()))
+    }
+}
+
+fn encode(value: &str, allow_reserved: bool, output: &mut String) {
+    let bytes = value.as_bytes();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let byte = bytes[i];
+        let unreserved = byte.is_ascii_alphanumeric() || b"-._~".contains(&byte);
+        let reserved = b":/?#[]@!$&'()*+,;=".contains(&byte);
+
+        if unreserved || (allow_reserved && reserved) {
+            output.push(byte as char);
+        } else if allow_reserved && byte == b'%' && is_pct_triplet(&bytes[i..]) {
+            // Reserved expansion keeps existing percent-encoded triplets.
+            output.push_str(&value[i..i + 3]);
+            i += 3;
+            continue;
+        } else {
+            output.push_str(&format!("%{:02X}", byte));
+        }
+        i += 1;
+    }
+}
+
+fn is_pct_triplet(bytes: &[u8]) -> bool {
+    bytes.len() >= 3 && bytes[1].is_ascii_hexdigit() && bytes[2].is_ascii_hexdigit()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The variables used by the examples in RFC 6570, section 1.2.
+    fn rfc_variables() -> HashMap<String, String> {
+        [
+            ("var", "value"),
+            ("hello", "Hello World!"),
+            ("path", "/foo/bar"),
+            ("empty", ""),
+            ("x", "1024"),
+            ("y", "768"),
+        ]
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect()
+    }
+
+    fn assert_expands(cases: &[(&str, &str)]) {
+        let variables = rfc_variables();
+        for (template, expected) in cases {
+            assert_eq!(expand(template, &variables).as_deref(), Ok(*expected), "{}", template);
+        }
+    }
+
+    #[test]
+    fn level_1() {
+        assert_expands(&[("{var}", "value"), ("{hello}", "Hello%20World%21")]);
+    }
+
+    #[test]
+    fn level_2() {
+        assert_expands(&[
+            ("{+var}", "value"),
+            ("{+hello}", "Hello%20World!"),
+            ("{+path}/here", "/foo/bar/here"),
+            ("here?ref={+path}", "here?ref=/foo/bar"),
+            ("X{#var}", "X#value"),
+            ("X{#hello}", "X#Hello%20World!"),
+        ]);
+    }
+
+    #[test]
+    fn level_3() {
+        assert_expands(&[
+            ("map?{x,y}", "map?1024,768"),
+            ("{x,hello,y}", "1024,Hello%20World%21,768"),
+            ("{+x,hello,y}", "1024,Hello%20World!,768"),
+            ("{+path,x}/here", "/foo/bar,1024/here"),
+            ("{#x,hello,y}", "#1024,Hello%20World!,768"),
+            ("{#path,x}/here", "#/foo/bar,1024/here"),
+            ("X{.var}", "X.value"),
+            ("X{.x,y}", "X.1024.768"),
+            ("{/var}", "/value"),
+            ("{/var,x}/here", "/value/1024/here"),
+            ("{;x,y}", ";x=1024;y=768"),
+            ("{;x,y,empty}", ";x=1024;y=768;empty"),
+            ("{?x,y}", "?x=1024&y=768"),
+            ("{?x,y,empty}", "?x=1024&y=768&empty="),
+            ("?fixed=yes{&x}", "?fixed=yes&x=1024"),
+            ("{&x,y,empty}", "&x=1024&y=768&empty="),
+        ]);
+    }
+
+    #[test]
+    fn undefined_variables_are_left_out() {
+        assert_expands(&[("{undef}", ""), ("{?undef,x}", "?x=1024"), ("{/undef}/here", "/here")]);
+    }
+
+    #[test]
+    fn keeps_percent_triplets_only_in_reserved_expansion() {
+        let variables = HashMap::from([("v".to_string(), "a%20b".to_string())]);
+        assert_eq!(expand("{+v}", &variables).as_deref(), Ok("a%20b"));
+        assert_eq!(expand("{v}", &variables).as_deref(), Ok("a%2520b"));
+    }
+
+    #[test]
+    fn rejects_level_4_modifiers() {
+        let variables = rfc_variables();
+        assert_eq!(
+            expand("{var:3}", &variables),
+            Err(UriTemplateError::UnsupportedExpression("var:3".to_string()))
+        );
+        assert_eq!(
+            expand("{var*}", &variables),
+            Err(UriTemplateError::UnsupportedExpression("var*".to_string()))
+        );
+    }
+
+    #[test]
+    fn rejects_unclosed_expressions() {
+        assert_eq!(
+            expand("file:///{path", &rfc_variables()),
+            Err(UriTemplateError::UnclosedExpression("file:///{path".to_string()))
+        );
+    }
+
+    #[test]
+    fn lists_variable_names_once_in_order() {
+        assert_eq!(
+            variable_names("{/a,b}{?b,c}").unwrap(),
+            vec!["a".to_string(), "b".to_string(), "c".to_string()]
+        );
+        assert!(variable_names("{a*}").is_err());
+    }
+}